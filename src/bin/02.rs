@@ -1,3 +1,4 @@
+use advent_of_code::parse::{parse_all, ParseError, Parser};
 use std::str::FromStr;
 
 advent_of_code::solution!(2);
@@ -47,56 +48,63 @@ impl Game {
     }
 }
 
-#[derive(Debug, PartialEq)]
-struct ParseGameError;
-
-impl FromStr for Draw {
-    type Err = ParseGameError;
+#[derive(Clone, Copy)]
+enum Colour {
+    Red,
+    Green,
+    Blue,
+}
 
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
+impl Draw {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut draw = Self {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
 
-        for colour_draw in text.split(", ") {
-            if let Some((qty_str, colour)) = colour_draw.split_once(' ') {
-                let quantity = qty_str.parse().map_err(|_| ParseGameError)?;
-                match colour {
-                    "red" => red = quantity,
-                    "green" => green = quantity,
-                    "blue" => blue = quantity,
-                    _ => return Err(ParseGameError),
-                }
-            } else {
-                return Err(ParseGameError);
+        parser.separated_by(", ", |parser| {
+            let quantity = parser.number()?;
+            parser.keyword(" ")?;
+            match parser.one_of(&[
+                ("red", Colour::Red),
+                ("green", Colour::Green),
+                ("blue", Colour::Blue),
+            ])? {
+                Colour::Red => draw.red = quantity,
+                Colour::Green => draw.green = quantity,
+                Colour::Blue => draw.blue = quantity,
             }
-        }
+            Ok(())
+        })?;
 
-        Ok(Self { red, green, blue })
+        Ok(draw)
     }
 }
 
-impl FromStr for Game {
-    type Err = ParseGameError;
+impl FromStr for Draw {
+    type Err = ParseError;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        if let Some((id_str, draws_str)) = text.split_once(": ") {
-            if let Some(id_str) = id_str.strip_prefix("Game ") {
-                let id = id_str.parse().map_err(|_| ParseGameError)?;
-                let mut draws = Vec::new();
+        parse_all(text, Self::parse)
+    }
+}
 
-                for draw_str in draws_str.split("; ") {
-                    let draw = draw_str.parse()?;
-                    draws.push(draw);
-                }
+impl Game {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.keyword("Game ")?;
+        let id = parser.number()?;
+        parser.keyword(": ")?;
+        let draws = parser.separated_by("; ", Draw::parse)?;
+        Ok(Self { id, draws })
+    }
+}
 
-                Ok(Self { id, draws })
-            } else {
-                Err(ParseGameError)
-            }
-        } else {
-            Err(ParseGameError)
-        }
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        parse_all(text, Self::parse)
     }
 }
 
@@ -171,7 +179,11 @@ mod tests {
     fn test_parse_game_error() {
         assert_eq!(
             "Game 1: 3 blu, 4 red; 1 red, 2 green, and 6 blue; 2G".parse::<Game>(),
-            Err(ParseGameError)
+            Err(ParseError {
+                line: 1,
+                column: 11,
+                expected: "one of `red`, `green`, `blue`".to_string(),
+            })
         );
     }
 
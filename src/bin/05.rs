@@ -1,7 +1,8 @@
-use std::collections::BTreeSet;
+use advent_of_code::intervals::{Interval, IntervalSet, PiecewiseMap};
+use advent_of_code::parse::{parse_all, ParseError, Parser};
 use std::str::FromStr;
 
-advent_of_code::solution!(5);
+advent_of_code::solution!(5, explain);
 
 #[derive(Debug, PartialEq)]
 struct AlmanacMapEntry {
@@ -21,56 +22,29 @@ impl AlmanacMapEntry {
 }
 
 #[derive(Debug, PartialEq)]
-struct AlmanacMap(Vec<AlmanacMapEntry>);
-
-#[derive(Debug, PartialEq)]
-struct ValueRange {
-    start: u64,
-    length: u64,
+struct AlmanacMap {
+    destination: String,
+    entries: Vec<AlmanacMapEntry>,
 }
 
 impl AlmanacMap {
     fn convert(&self, source: u64) -> u64 {
-        self.0
+        self.entries
             .iter()
             .map(|entry| entry.convert(source))
             .find_map(|e| e)
             .unwrap_or(source)
     }
 
-    fn convert_range(&self, range: &ValueRange) -> Vec<ValueRange> {
-        let mut slices = BTreeSet::new();
-        let range_end = range.start + range.length;
-
-        for entry in &self.0 {
-            let source_end = entry.source_start + entry.length;
-
-            if range_end < entry.source_start || range.start > source_end {
-                continue;
-            }
-
-            if entry.source_start > range.start {
-                slices.insert(entry.source_start);
-            }
-
-            if source_end < range_end {
-                slices.insert(source_end);
-            }
+    fn piecewise(&self) -> PiecewiseMap<u64> {
+        let mut map = PiecewiseMap::identity();
+        for entry in &self.entries {
+            map.insert(
+                Interval::new(entry.source_start, entry.source_start + entry.length),
+                entry.dest_start,
+            );
         }
-        slices.insert(range_end);
-
-        let mut output = Vec::new();
-        let mut current = range.start;
-
-        for position in slices {
-            output.push(ValueRange {
-                start: self.convert(current),
-                length: position - current,
-            });
-            current = position;
-        }
-
-        output
+        map
     }
 }
 
@@ -85,87 +59,90 @@ impl Almanac {
         self.maps.iter().fold(seed, |value, map| map.convert(value))
     }
 
-    fn seed_ranges(&self) -> impl Iterator<Item = ValueRange> + '_ {
-        (0..self.seeds.len()).step_by(2).map(|ix| ValueRange {
-            start: self.seeds[ix],
-            length: self.seeds[ix + 1],
-        })
+    fn seed_ranges(&self) -> impl Iterator<Item = Interval<u64>> + '_ {
+        (0..self.seeds.len())
+            .step_by(2)
+            .map(|ix| Interval::new(self.seeds[ix], self.seeds[ix] + self.seeds[ix + 1]))
     }
-}
-
-#[derive(Debug, PartialEq)]
-struct ParseAlmanacError;
-
-impl FromStr for Almanac {
-    type Err = ParseAlmanacError;
 
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let mut seeds = Vec::new();
-        let mut maps = Vec::new();
-
-        for (ix, section) in text.split("\n\n").enumerate() {
-            if ix == 0 {
-                if let Some(seeds_str) = section.strip_prefix("seeds: ") {
-                    for seed in seeds_str.split_whitespace().map(u64::from_str) {
-                        let seed = seed.map_err(|_| ParseAlmanacError)?;
-                        seeds.push(seed);
-                    }
-                } else {
-                    return Err(ParseAlmanacError);
-                }
-            } else {
-                let map: AlmanacMap = section.parse()?;
-                maps.push(map);
-            }
+    /// The value of each category for the given seed, from the seed itself through to its
+    /// location.
+    fn chain(&self, seed: u64) -> Vec<(&str, u64)> {
+        let mut chain = vec![("seed", seed)];
+        let mut value = seed;
+        for map in &self.maps {
+            value = map.convert(value);
+            chain.push((&map.destination, value));
         }
-
-        Ok(Self { seeds, maps })
+        chain
     }
-}
 
-impl FromStr for AlmanacMap {
-    type Err = ParseAlmanacError;
+    fn seed_to_location_map(&self) -> PiecewiseMap<u64> {
+        let maps: Vec<PiecewiseMap<u64>> = self.maps.iter().map(AlmanacMap::piecewise).collect();
+        PiecewiseMap::compose(&maps)
+    }
 
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let mut entries = Vec::new();
+    /// The seed in the seed ranges with the lowest location. Within each piece of the composed
+    /// map locations increase along with seeds, so only the start of each seed range and the
+    /// breakpoints falling inside the ranges need to be checked.
+    fn lowest_location_seed(&self) -> Option<u64> {
+        let seeds: IntervalSet<u64> = self.seed_ranges().collect();
+        let map = self.seed_to_location_map();
+        let breakpoints = map.breakpoints();
 
-        for (ix, line) in text.lines().enumerate() {
-            if ix == 0 {
-                continue;
-            }
+        seeds
+            .iter()
+            .map(|range| range.start)
+            .chain(breakpoints.into_iter().filter(|seed| seeds.contains(*seed)))
+            .min_by_key(|seed| map.map(*seed))
+    }
 
-            let entry: AlmanacMapEntry = line.parse()?;
-            entries.push(entry);
-        }
+    fn lowest_location(&self) -> Option<u64> {
+        self.lowest_location_seed()
+            .map(|seed| self.seed_to_location(seed))
+    }
+}
 
-        Ok(Self(entries))
+impl Almanac {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.keyword("seeds: ")?;
+        let seeds = parser.list(Parser::number)?;
+        parser.keyword("\n\n")?;
+        let maps = parser.blocks(AlmanacMap::parse)?;
+        Ok(Self { seeds, maps })
     }
 }
 
-impl FromStr for AlmanacMapEntry {
-    type Err = ParseAlmanacError;
+impl FromStr for Almanac {
+    type Err = ParseError;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let mut dest_start: Result<u64, Self::Err> = Err(ParseAlmanacError);
-        let mut source_start: Result<u64, Self::Err> = Err(ParseAlmanacError);
-        let mut length: Result<u64, Self::Err> = Err(ParseAlmanacError);
-
-        for (ix, value) in text
-            .split_whitespace()
-            .map(|value| u64::from_str(value).map_err(|_| ParseAlmanacError))
-            .enumerate()
-        {
-            match ix {
-                0 => dest_start = value,
-                1 => source_start = value,
-                2 => length = value,
-                _ => return Err(ParseAlmanacError),
-            }
-        }
+        parse_all(text, Self::parse)
+    }
+}
+
+impl AlmanacMap {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.take_while(|c| c != '-');
+        parser.keyword("-to-")?;
+        let destination = parser.take_while(|c| c != ' ').to_string();
+        parser.keyword(" map:\n")?;
+        let entries = parser.lines(AlmanacMapEntry::parse)?;
+        Ok(Self {
+            destination,
+            entries,
+        })
+    }
+}
 
-        let dest_start = dest_start?;
-        let source_start = source_start?;
-        let length = length?;
+impl AlmanacMapEntry {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let dest_start = parser.number()?;
+        parser.whitespace();
+        let source_start = parser.number()?;
+        parser.whitespace();
+        let length = parser.number()?;
+        parser.whitespace();
 
         Ok(Self {
             dest_start,
@@ -190,21 +167,45 @@ pub fn part_one(input: &str) -> Option<u64> {
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u64> {
-    if let Ok(almanac) = input.parse::<Almanac>() {
-        let mut current: Vec<ValueRange> = almanac.seed_ranges().collect();
-        let mut future = Vec::new();
-
-        for map in almanac.maps {
-            for range in current {
-                future.extend(map.convert_range(&range));
-            }
-            current = future;
-            future = Vec::new();
+    input
+        .parse::<Almanac>()
+        .ok()
+        .and_then(|almanac| almanac.lowest_location())
+}
+
+fn format_chain(chain: &[(&str, u64)]) -> String {
+    chain
+        .iter()
+        .map(|(category, value)| format!("{category} {value}"))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// With `--explain`, shows which seed gives the lowest location in each part, along with the
+/// values of all the categories in between.
+fn explain(input: &str) {
+    if !advent_of_code::template::runner::has_flag("--explain") {
+        return;
+    }
+    let almanac = match input.parse::<Almanac>() {
+        Ok(almanac) => almanac,
+        Err(e) => {
+            eprintln!("Failed to read almanac: {e}");
+            return;
         }
+    };
+
+    if let Some(seed) = almanac
+        .seeds
+        .iter()
+        .copied()
+        .min_by_key(|seed| almanac.seed_to_location(*seed))
+    {
+        println!("Part 1: {}", format_chain(&almanac.chain(seed)));
+    }
 
-        current.iter().map(|range| range.start).min()
-    } else {
-        None
+    if let Some(seed) = almanac.lowest_location_seed() {
+        println!("Part 2: {}", format_chain(&almanac.chain(seed)));
     }
 }
 
@@ -212,114 +213,31 @@ pub fn part_two(input: &str) -> Option<u64> {
 mod tests {
     use super::*;
 
+    fn almanac_map(destination: &str, entries: &[(u64, u64, u64)]) -> AlmanacMap {
+        AlmanacMap {
+            destination: destination.to_string(),
+            entries: entries
+                .iter()
+                .map(|&(dest_start, source_start, length)| AlmanacMapEntry {
+                    dest_start,
+                    source_start,
+                    length,
+                })
+                .collect(),
+        }
+    }
+
     fn example_almanac() -> Almanac {
         Almanac {
             seeds: vec![79, 14, 55, 13],
             maps: vec![
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 50,
-                        source_start: 98,
-                        length: 2,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 52,
-                        source_start: 50,
-                        length: 48,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 0,
-                        source_start: 15,
-                        length: 37,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 37,
-                        source_start: 52,
-                        length: 2,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 39,
-                        source_start: 0,
-                        length: 15,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 49,
-                        source_start: 53,
-                        length: 8,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 0,
-                        source_start: 11,
-                        length: 42,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 42,
-                        source_start: 0,
-                        length: 7,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 57,
-                        source_start: 7,
-                        length: 4,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 88,
-                        source_start: 18,
-                        length: 7,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 18,
-                        source_start: 25,
-                        length: 70,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 45,
-                        source_start: 77,
-                        length: 23,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 81,
-                        source_start: 45,
-                        length: 19,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 68,
-                        source_start: 64,
-                        length: 13,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 0,
-                        source_start: 69,
-                        length: 1,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 1,
-                        source_start: 0,
-                        length: 69,
-                    },
-                ]),
-                AlmanacMap(vec![
-                    AlmanacMapEntry {
-                        dest_start: 60,
-                        source_start: 56,
-                        length: 37,
-                    },
-                    AlmanacMapEntry {
-                        dest_start: 56,
-                        source_start: 93,
-                        length: 4,
-                    },
-                ]),
+                almanac_map("soil", &[(50, 98, 2), (52, 50, 48)]),
+                almanac_map("fertilizer", &[(0, 15, 37), (37, 52, 2), (39, 0, 15)]),
+                almanac_map("water", &[(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)]),
+                almanac_map("light", &[(88, 18, 7), (18, 25, 70)]),
+                almanac_map("temperature", &[(45, 77, 23), (81, 45, 19), (68, 64, 13)]),
+                almanac_map("humidity", &[(0, 69, 1), (1, 0, 69)]),
+                almanac_map("location", &[(60, 56, 37), (56, 93, 4)]),
             ],
         }
     }
@@ -332,20 +250,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_input_error() {
+        assert_eq!(
+            "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 fifty 48".parse::<Almanac>(),
+            Err(ParseError {
+                line: 5,
+                column: 4,
+                expected: "number".to_string(),
+            })
+        );
+    }
+
     #[test]
     fn test_seed_to_soil_map() {
-        let map = AlmanacMap(vec![
-            AlmanacMapEntry {
-                dest_start: 50,
-                source_start: 98,
-                length: 2,
-            },
-            AlmanacMapEntry {
-                dest_start: 52,
-                source_start: 50,
-                length: 48,
-            },
-        ]);
+        let map = almanac_map("soil", &[(50, 98, 2), (52, 50, 48)]);
         assert_eq!(map.convert(79), 81);
         assert_eq!(map.convert(14), 14);
         assert_eq!(map.convert(55), 57);
@@ -361,6 +280,23 @@ mod tests {
         assert_eq!(almanac.seed_to_location(13), 35);
     }
 
+    #[test]
+    fn test_chain() {
+        assert_eq!(
+            example_almanac().chain(79),
+            vec![
+                ("seed", 79),
+                ("soil", 81),
+                ("fertilizer", 81),
+                ("water", 81),
+                ("light", 74),
+                ("temperature", 78),
+                ("humidity", 78),
+                ("location", 82),
+            ]
+        );
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -369,45 +305,65 @@ mod tests {
 
     #[test]
     fn test_seed_to_soil_map_range() {
-        let map = AlmanacMap(vec![
-            AlmanacMapEntry {
-                dest_start: 50,
-                source_start: 98,
-                length: 2,
-            },
-            AlmanacMapEntry {
-                dest_start: 52,
-                source_start: 50,
-                length: 48,
-            },
-        ]);
+        let map = almanac_map("soil", &[(50, 98, 2), (52, 50, 48)]);
+        assert_eq!(
+            map.piecewise().map_interval(Interval::new(50, 55)),
+            vec![Interval::new(52, 57)]
+        );
         assert_eq!(
-            map.convert_range(&ValueRange {
-                start: 50,
-                length: 5
-            }),
-            vec![ValueRange {
-                start: 52,
-                length: 5
-            }]
+            map.piecewise().map_interval(Interval::new(95, 102)),
+            vec![
+                Interval::new(97, 100),
+                Interval::new(50, 52),
+                Interval::new(100, 102)
+            ]
         );
     }
 
+    #[test]
+    fn test_seed_to_location_map() {
+        let almanac = example_almanac();
+        let map = almanac.seed_to_location_map();
+        for seed in 0..120 {
+            assert_eq!(map.map(seed), almanac.seed_to_location(seed));
+        }
+    }
+
+    #[test]
+    fn test_location_to_seed_map() {
+        let almanac = example_almanac();
+        let inverse = almanac
+            .seed_to_location_map()
+            .inverse()
+            .expect("example almanac maps are invertible");
+        for seed in 0..120 {
+            assert_eq!(inverse.map(almanac.seed_to_location(seed)), seed);
+        }
+        assert_eq!(inverse.map(46), 82);
+    }
+
+    #[test]
+    fn test_lowest_location() {
+        assert_eq!(example_almanac().lowest_location(), Some(46));
+        assert_eq!(example_almanac().lowest_location_seed(), Some(82));
+
+        // two seed ranges map onto the same locations, so the map has no inverse, but the seed
+        // with the lowest location can still be found.
+        let almanac = Almanac {
+            seeds: vec![3, 7],
+            maps: vec![almanac_map("location", &[(100, 0, 5), (100, 5, 5)])],
+        };
+        assert_eq!(almanac.seed_to_location_map().inverse(), None);
+        assert_eq!(almanac.lowest_location_seed(), Some(5));
+        assert_eq!(almanac.lowest_location(), Some(100));
+    }
+
     #[test]
     fn test_seed_ranges() {
-        let seed_ranges: Vec<ValueRange> = example_almanac().seed_ranges().collect();
+        let seed_ranges: Vec<Interval<u64>> = example_almanac().seed_ranges().collect();
         assert_eq!(
             seed_ranges,
-            vec![
-                ValueRange {
-                    start: 79,
-                    length: 14
-                },
-                ValueRange {
-                    start: 55,
-                    length: 13
-                },
-            ]
+            vec![Interval::new(79, 93), Interval::new(55, 68)]
         );
     }
 
@@ -1,8 +1,6 @@
-use std::str::FromStr;
+advent_of_code::solution!(7, debug);
 
-advent_of_code::solution!(7);
-
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 enum HandType {
     HighCard,
     OnePair,
@@ -14,235 +12,235 @@ enum HandType {
 }
 
 impl HandType {
-    fn from_cards(cards: [usize; 5]) -> Self {
-        let mut counts: [usize; 15] = [0; 15];
-        for card in cards {
-            counts[card] += 1;
-        }
-        let (values, max) = counts[1..].iter().fold((0, 0), |(values, max), value| {
-            (values + usize::from(value > &0), max.max(*value))
-        });
-
-        match max + counts[0] {
-            5 => Self::FiveOfAKind,
-            4 => Self::FourOfAKind,
-            3 => {
-                if values == 2 {
-                    Self::FullHouse
-                } else {
-                    Self::ThreeOfAKind
-                }
-            }
-            2 => {
-                if values == 3 {
-                    Self::TwoPair
-                } else {
-                    Self::OnePair
-                }
-            }
+    /// Classifies a hand from the sizes of its two largest groups of matching cards.
+    const fn from_groups(largest: usize, second: usize) -> Self {
+        match (largest, second) {
+            (5.., _) => Self::FiveOfAKind,
+            (4, _) => Self::FourOfAKind,
+            (3, 2..) => Self::FullHouse,
+            (3, _) => Self::ThreeOfAKind,
+            (2, 2..) => Self::TwoPair,
+            (2, _) => Self::OnePair,
             _ => Self::HighCard,
         }
     }
 }
 
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
+/// How two hands of the same type are ordered.
+#[derive(Debug, PartialEq)]
+enum TieBreak {
+    /// Compare the cards one at a time, in the order they were dealt.
+    InOrder,
+    /// Compare the largest groups of cards first (higher cards first within groups of the same
+    /// size), as in poker.
+    ByGroup,
+}
+
+/// A set of rules for classifying and ranking hands.
+#[derive(Debug, PartialEq)]
+struct Rules {
+    /// The card labels, from weakest to strongest.
+    order: &'static str,
+    /// Cards which join whichever group makes the hand strongest.
+    wildcards: &'static str,
+    hand_size: usize,
+    tie_break: TieBreak,
+}
+
+const STANDARD: Rules = Rules {
+    order: "23456789TJQKA",
+    wildcards: "",
+    hand_size: 5,
+    tie_break: TieBreak::InOrder,
+};
+
+const JOKERS: Rules = Rules {
+    order: "J23456789TQKA",
+    wildcards: "J",
+    hand_size: 5,
+    tie_break: TieBreak::InOrder,
+};
+
+#[derive(Debug, PartialEq)]
 struct Hand {
+    cards: String,
     htype: HandType,
-    cards: [usize; 5],
     bid: usize,
 }
 
 #[derive(Debug, PartialEq)]
 struct ParseHandError;
 
-impl FromStr for Hand {
-    type Err = ParseHandError;
+impl Rules {
+    fn strength(&self, card: char) -> usize {
+        self.order.find(card).unwrap_or_default()
+    }
 
-    fn from_str(line: &str) -> Result<Self, Self::Err> {
-        if let Some((cards_str, bid_str)) = line.split_once(' ') {
-            if cards_str.len() != 5 {
-                return Err(ParseHandError);
+    fn classify(&self, cards: &str) -> HandType {
+        let mut counts = vec![0; self.order.len()];
+        let mut wildcards = 0;
+        for card in cards.chars() {
+            if self.wildcards.contains(card) {
+                wildcards += 1;
+            } else {
+                counts[self.strength(card)] += 1;
             }
+        }
+        counts.sort_unstable_by(|a, b| b.cmp(a));
 
-            let mut cards = [0; 5];
-            for (ix, card) in cards_str.chars().enumerate() {
-                if ix >= 5 {
-                    return Err(ParseHandError);
-                }
-
-                let card = match card {
-                    'j' => Ok(0),
-                    '2' => Ok(2),
-                    '3' => Ok(3),
-                    '4' => Ok(4),
-                    '5' => Ok(5),
-                    '6' => Ok(6),
-                    '7' => Ok(7),
-                    '8' => Ok(8),
-                    '9' => Ok(9),
-                    'T' => Ok(10),
-                    'J' => Ok(11),
-                    'Q' => Ok(12),
-                    'K' => Ok(13),
-                    'A' => Ok(14),
-                    _ => Err(ParseHandError),
-                }?;
-                cards[ix] = card;
-            }
+        let largest = counts.first().copied().unwrap_or_default();
+        let second = counts.get(1).copied().unwrap_or_default();
+        HandType::from_groups(largest + wildcards, second)
+    }
+
+    /// The strengths of the cards in the order they're compared to break ties.
+    fn tie_break_key(&self, cards: &str) -> Vec<usize> {
+        let mut key: Vec<usize> = cards.chars().map(|card| self.strength(card)).collect();
+        if self.tie_break == TieBreak::ByGroup {
+            let count = |strength: usize| key.iter().filter(|other| **other == strength).count();
+            let mut grouped = key.clone();
+            grouped
+                .sort_unstable_by_key(|strength| std::cmp::Reverse((count(*strength), *strength)));
+            key = grouped;
+        }
+        key
+    }
 
-            let bid = bid_str.parse().map_err(|_| ParseHandError)?;
-            let htype = HandType::from_cards(cards);
+    fn parse_hand(&self, line: &str) -> Result<Hand, ParseHandError> {
+        let (cards, bid) = line.split_once(' ').ok_or(ParseHandError)?;
 
-            Ok(Self { htype, cards, bid })
-        } else {
-            Err(ParseHandError)
+        if cards.chars().count() != self.hand_size
+            || !cards.chars().all(|card| self.order.contains(card))
+        {
+            return Err(ParseHandError);
         }
+
+        Ok(Hand {
+            cards: cards.to_string(),
+            htype: self.classify(cards),
+            bid: bid.parse().map_err(|_| ParseHandError)?,
+        })
     }
-}
 
-fn read_hands(input: &str) -> Result<Vec<Hand>, ParseHandError> {
-    let mut hands = Vec::new();
-    for line in input.lines() {
-        let hand = line.parse()?;
-        hands.push(hand);
+    fn read_hands(&self, input: &str) -> Result<Vec<Hand>, ParseHandError> {
+        input.lines().map(|line| self.parse_hand(line)).collect()
+    }
+
+    /// Sorts the hands from weakest to strongest, so that each hand's rank is its index plus one.
+    fn rank(&self, hands: &mut [Hand]) {
+        hands.sort_by_cached_key(|hand| (hand.htype, self.tie_break_key(&hand.cards)));
     }
-    Ok(hands)
-}
 
-fn read_joker_hands(input: &str) -> Result<Vec<Hand>, ParseHandError> {
-    read_hands(&input.replace('J', "j"))
+    fn total_winnings(&self, input: &str) -> Option<usize> {
+        self.read_hands(input).map_or(None, |mut hands| {
+            self.rank(&mut hands);
+            Some(
+                hands
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, hand)| (ix + 1) * hand.bid)
+                    .sum(),
+            )
+        })
+    }
 }
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    read_hands(input).map_or(None, |mut hands| {
-        hands.sort_unstable();
-        Some(
-            hands
-                .iter()
-                .enumerate()
-                .map(|(ix, hand)| (ix + 1) * hand.bid)
-                .sum(),
-        )
-    })
+    STANDARD.total_winnings(input)
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    read_joker_hands(input).map_or(None, |mut hands| {
-        hands.sort_unstable();
-        Some(
-            hands
-                .iter()
-                .enumerate()
-                .map(|(ix, hand)| (ix + 1) * hand.bid)
-                .sum(),
-        )
-    })
+    JOKERS.total_winnings(input)
+}
+
+/// With `--debug`, prints every hand with its type and final rank under each part's rules.
+fn debug(input: &str) {
+    if !advent_of_code::template::runner::has_flag("--debug") {
+        return;
+    }
+
+    for (part, rules) in [(1, &STANDARD), (2, &JOKERS)] {
+        let Ok(mut hands) = rules.read_hands(input) else {
+            eprintln!("Part {part}: failed to read hands");
+            continue;
+        };
+        rules.rank(&mut hands);
+
+        println!("Part {part}:");
+        for (ix, hand) in hands.iter().enumerate() {
+            println!(
+                "{:>6}  {}  {:<12}  bid {}",
+                ix + 1,
+                hand.cards,
+                format!("{:?}", hand.htype),
+                hand.bid
+            );
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn hand(cards: &str, htype: HandType, bid: usize) -> Hand {
+        Hand {
+            cards: cards.to_string(),
+            htype,
+            bid,
+        }
+    }
+
     fn example_hands() -> Vec<Hand> {
         vec![
-            Hand {
-                cards: [3, 2, 10, 3, 13],
-                htype: HandType::OnePair,
-                bid: 765,
-            },
-            Hand {
-                cards: [10, 5, 5, 11, 5],
-                htype: HandType::ThreeOfAKind,
-                bid: 684,
-            },
-            Hand {
-                cards: [13, 13, 6, 7, 7],
-                htype: HandType::TwoPair,
-                bid: 28,
-            },
-            Hand {
-                cards: [13, 10, 11, 11, 10],
-                htype: HandType::TwoPair,
-                bid: 220,
-            },
-            Hand {
-                cards: [12, 12, 12, 11, 14],
-                htype: HandType::ThreeOfAKind,
-                bid: 483,
-            },
+            hand("32T3K", HandType::OnePair, 765),
+            hand("T55J5", HandType::ThreeOfAKind, 684),
+            hand("KK677", HandType::TwoPair, 28),
+            hand("KTJJT", HandType::TwoPair, 220),
+            hand("QQQJA", HandType::ThreeOfAKind, 483),
         ]
     }
 
     #[test]
     fn test_parse_hand() {
         assert_eq!(
-            "32T3K 765".parse(),
-            Ok(Hand {
-                cards: [3, 2, 10, 3, 13],
-                htype: HandType::OnePair,
-                bid: 765,
-            }),
+            STANDARD.parse_hand("32T3K 765"),
+            Ok(hand("32T3K", HandType::OnePair, 765)),
         );
         assert_eq!(
-            "T55J5 684".parse(),
-            Ok(Hand {
-                cards: [10, 5, 5, 11, 5],
-                htype: HandType::ThreeOfAKind,
-                bid: 684,
-            }),
+            STANDARD.parse_hand("T55J5 684"),
+            Ok(hand("T55J5", HandType::ThreeOfAKind, 684)),
         );
         assert_eq!(
-            "KK677 28".parse(),
-            Ok(Hand {
-                cards: [13, 13, 6, 7, 7],
-                htype: HandType::TwoPair,
-                bid: 28,
-            }),
-        )
+            STANDARD.parse_hand("KK677 28"),
+            Ok(hand("KK677", HandType::TwoPair, 28)),
+        );
+        assert_eq!(STANDARD.parse_hand("KK67 28"), Err(ParseHandError));
+        assert_eq!(STANDARD.parse_hand("KK67X 28"), Err(ParseHandError));
     }
 
     #[test]
     fn test_read_hands() {
         assert_eq!(
-            read_hands(&advent_of_code::template::read_file("examples", DAY)),
+            STANDARD.read_hands(&advent_of_code::template::read_file("examples", DAY)),
             Ok(example_hands())
         );
     }
 
     #[test]
-    fn test_sort_hands() {
+    fn test_rank_hands() {
         let mut hands = example_hands();
-        hands.sort_unstable();
+        STANDARD.rank(&mut hands);
         assert_eq!(
             hands,
             vec![
-                Hand {
-                    cards: [3, 2, 10, 3, 13],
-                    htype: HandType::OnePair,
-                    bid: 765
-                },
-                Hand {
-                    cards: [13, 10, 11, 11, 10],
-                    htype: HandType::TwoPair,
-                    bid: 220
-                },
-                Hand {
-                    cards: [13, 13, 6, 7, 7],
-                    htype: HandType::TwoPair,
-                    bid: 28
-                },
-                Hand {
-                    cards: [10, 5, 5, 11, 5],
-                    htype: HandType::ThreeOfAKind,
-                    bid: 684
-                },
-                Hand {
-                    cards: [12, 12, 12, 11, 14],
-                    htype: HandType::ThreeOfAKind,
-                    bid: 483
-                },
+                hand("32T3K", HandType::OnePair, 765),
+                hand("KTJJT", HandType::TwoPair, 220),
+                hand("KK677", HandType::TwoPair, 28),
+                hand("T55J5", HandType::ThreeOfAKind, 684),
+                hand("QQQJA", HandType::ThreeOfAKind, 483),
             ]
         );
     }
@@ -256,69 +254,37 @@ mod tests {
     #[test]
     fn test_parse_joker_hand() {
         assert_eq!(
-            "T55j5 684".parse(),
-            Ok(Hand {
-                cards: [10, 5, 5, 0, 5],
-                htype: HandType::FourOfAKind,
-                bid: 684,
-            }),
+            JOKERS.parse_hand("T55J5 684"),
+            Ok(hand("T55J5", HandType::FourOfAKind, 684)),
         );
         assert_eq!(
-            "KTjjT 220".parse(),
-            Ok(Hand {
-                cards: [13, 10, 0, 0, 10],
-                htype: HandType::FourOfAKind,
-                bid: 220,
-            }),
+            JOKERS.parse_hand("KTJJT 220"),
+            Ok(hand("KTJJT", HandType::FourOfAKind, 220)),
         );
         assert_eq!(
-            "234jj 137".parse(),
-            Ok(Hand {
-                cards: [2, 3, 4, 0, 0],
-                htype: HandType::ThreeOfAKind,
-                bid: 137,
-            }),
+            JOKERS.parse_hand("234JJ 137"),
+            Ok(hand("234JJ", HandType::ThreeOfAKind, 137)),
         );
         assert_eq!(
-            "2345j 9652".parse(),
-            Ok(Hand {
-                cards: [2, 3, 4, 5, 0],
-                htype: HandType::OnePair,
-                bid: 9652,
-            }),
-        )
+            JOKERS.parse_hand("2345J 9652"),
+            Ok(hand("2345J", HandType::OnePair, 9652)),
+        );
+        assert_eq!(
+            JOKERS.parse_hand("JJJJJ 1"),
+            Ok(hand("JJJJJ", HandType::FiveOfAKind, 1)),
+        );
     }
 
     #[test]
     fn test_read_joker_hands() {
         assert_eq!(
-            read_joker_hands(&advent_of_code::template::read_file("examples", DAY)),
+            JOKERS.read_hands(&advent_of_code::template::read_file("examples", DAY)),
             Ok(vec![
-                Hand {
-                    cards: [3, 2, 10, 3, 13],
-                    htype: HandType::OnePair,
-                    bid: 765,
-                },
-                Hand {
-                    cards: [10, 5, 5, 0, 5],
-                    htype: HandType::FourOfAKind,
-                    bid: 684,
-                },
-                Hand {
-                    cards: [13, 13, 6, 7, 7],
-                    htype: HandType::TwoPair,
-                    bid: 28,
-                },
-                Hand {
-                    cards: [13, 10, 0, 0, 10],
-                    htype: HandType::FourOfAKind,
-                    bid: 220,
-                },
-                Hand {
-                    cards: [12, 12, 12, 0, 14],
-                    htype: HandType::FourOfAKind,
-                    bid: 483,
-                },
+                hand("32T3K", HandType::OnePair, 765),
+                hand("T55J5", HandType::FourOfAKind, 684),
+                hand("KK677", HandType::TwoPair, 28),
+                hand("KTJJT", HandType::FourOfAKind, 220),
+                hand("QQQJA", HandType::FourOfAKind, 483),
             ]),
         );
     }
@@ -328,4 +294,34 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(5905));
     }
+
+    #[test]
+    fn test_variant_rules() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+
+        let poker = Rules {
+            tie_break: TieBreak::ByGroup,
+            ..STANDARD
+        };
+        assert_eq!(poker.total_winnings(&example), Some(6440));
+        assert_eq!(STANDARD.total_winnings("KAA2A 1\nAK333 2"), Some(5));
+        assert_eq!(poker.total_winnings("KAA2A 1\nAK333 2"), Some(4));
+
+        let short_hands = Rules {
+            order: "*23456789TJQKA",
+            wildcards: "*",
+            hand_size: 3,
+            tie_break: TieBreak::InOrder,
+        };
+        assert_eq!(
+            short_hands.parse_hand("K*K 3"),
+            Ok(hand("K*K", HandType::ThreeOfAKind, 3))
+        );
+        assert_eq!(
+            short_hands.parse_hand("2*3 7"),
+            Ok(hand("2*3", HandType::OnePair, 7))
+        );
+        assert_eq!(short_hands.total_winnings("K*K 3\n2*3 7\nA23 11"), Some(34));
+        assert_eq!(short_hands.read_hands("KKKK 1"), Err(ParseHandError));
+    }
 }
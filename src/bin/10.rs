@@ -1,9 +1,10 @@
+use advent_of_code::geometry::polygon::{Point, Polygon};
+use advent_of_code::image;
+use advent_of_code::render::{Cell, Colour, Frame, Style};
 use std::collections::VecDeque;
 use std::str::FromStr;
 
-advent_of_code::solution!(10);
-
-const GRID_SIZE: usize = 140;
+advent_of_code::solution!(10, extras);
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Direction {
@@ -45,6 +46,18 @@ impl Pipe {
             _ => None,
         }
     }
+
+    /// The box-drawing character with the same shape as the pipe.
+    const fn symbol(self) -> char {
+        match (self.0, self.1) {
+            (Direction::North, Direction::South) | (Direction::South, Direction::North) => '│',
+            (Direction::East, Direction::West) | (Direction::West, Direction::East) => '─',
+            (Direction::North, Direction::East) | (Direction::East, Direction::North) => '└',
+            (Direction::North, Direction::West) | (Direction::West, Direction::North) => '┘',
+            (Direction::South, Direction::West) | (Direction::West, Direction::South) => '┐',
+            _ => '┌',
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -55,28 +68,32 @@ struct EdgePosition {
 }
 
 impl EdgePosition {
-    const fn cross_edge(&self) -> Self {
+    /// Moves across the edge into the neighbouring cell of a `width` by `height` grid, unless
+    /// the edge is on the outside of the grid.
+    const fn cross_edge(&self, width: usize, height: usize) -> Option<Self> {
+        let (row, col) = (self.pos / width, self.pos % width);
         match self.edge {
-            Direction::North => Self {
-                pos: self.pos - GRID_SIZE,
+            Direction::North if row > 0 => Some(Self {
+                pos: self.pos - width,
                 edge: Direction::South,
                 steps: self.steps,
-            },
-            Direction::East => Self {
+            }),
+            Direction::East if col + 1 < width => Some(Self {
                 pos: self.pos + 1,
                 edge: Direction::West,
                 steps: self.steps,
-            },
-            Direction::South => Self {
-                pos: self.pos + GRID_SIZE,
+            }),
+            Direction::South if row + 1 < height => Some(Self {
+                pos: self.pos + width,
                 edge: Direction::North,
                 steps: self.steps,
-            },
-            Direction::West => Self {
+            }),
+            Direction::West if col > 0 => Some(Self {
                 pos: self.pos - 1,
                 edge: Direction::East,
                 steps: self.steps,
-            },
+            }),
+            _ => None,
         }
     }
 
@@ -124,19 +141,28 @@ struct CornerPosition {
 }
 
 impl CornerPosition {
-    fn adjacent_positions<'a>(&'a self, pipe: Option<&'a Pipe>) -> impl Iterator<Item = Self> + 'a {
+    fn adjacent_positions<'a>(
+        &'a self,
+        pipe: Option<&'a Pipe>,
+        width: usize,
+        height: usize,
+    ) -> impl Iterator<Item = Self> + 'a {
         // calculate adjacent locations, accounting for OOB
-        let north = self.pos.checked_sub(GRID_SIZE);
-        let east = if (self.pos % GRID_SIZE) == (GRID_SIZE - 1) {
+        let north = self.pos.checked_sub(width);
+        let east = if (self.pos % width) == (width - 1) {
             None
         } else {
             Some(self.pos + 1)
         };
-        let west = self.pos.checked_sub(1);
-        let south = if (self.pos / GRID_SIZE) == (GRID_SIZE - 1) {
+        let west = if self.pos.is_multiple_of(width) {
             None
         } else {
-            Some(self.pos + GRID_SIZE)
+            Some(self.pos - 1)
+        };
+        let south = if (self.pos / width) == (height - 1) {
+            None
+        } else {
+            Some(self.pos + width)
         };
 
         COMPASS.iter().filter_map(move |direction| {
@@ -213,11 +239,15 @@ impl CornerPosition {
 }
 
 #[derive(Debug, PartialEq)]
-struct CornerVisitTracker([u8; GRID_SIZE * GRID_SIZE]);
+struct CornerVisitTracker(Vec<u8>);
 
 impl CornerVisitTracker {
-    const fn new() -> Self {
-        Self([0; GRID_SIZE * GRID_SIZE])
+    fn new(size: usize) -> Self {
+        Self(vec![0; size])
+    }
+
+    fn is_unvisited(&self, pos: usize) -> bool {
+        self.0[pos] == 0
     }
 
     fn count_unvisited(&self) -> u32 {
@@ -240,29 +270,36 @@ impl CornerVisitTracker {
 }
 
 struct Maze {
+    width: usize,
+    height: usize,
     start: usize,
-    grid: [Option<Pipe>; GRID_SIZE * GRID_SIZE],
+    grid: Vec<Option<Pipe>>,
 }
 
 impl Maze {
+    const fn position(&self, row: usize, col: usize) -> usize {
+        (row * self.width) + col
+    }
+
     fn furthest_point_in_loop(&self) -> Option<u32> {
-        let mut visited = [false; GRID_SIZE * GRID_SIZE];
+        let mut visited = vec![false; self.grid.len()];
         let mut queue = VecDeque::new();
 
         for edge in COMPASS {
-            queue.push_back(
-                EdgePosition {
-                    pos: self.start,
-                    edge,
-                    steps: 0,
-                }
-                .cross_edge(),
-            );
+            let start = EdgePosition {
+                pos: self.start,
+                edge,
+                steps: 0,
+            };
+            queue.extend(start.cross_edge(self.width, self.height));
         }
 
         while let Some(pos) = queue.pop_front() {
             if let Some(pipe) = self.grid[pos.pos] {
-                if let Some(next) = pos.traverse_pipe(pipe).map(|p| p.cross_edge()) {
+                if let Some(next) = pos
+                    .traverse_pipe(pipe)
+                    .and_then(|p| p.cross_edge(self.width, self.height))
+                {
                     if visited[next.pos] {
                         return Some(next.steps + 1);
                     }
@@ -283,7 +320,7 @@ impl Maze {
                 edge: *edge,
                 steps: 0,
             }
-            .cross_edge();
+            .cross_edge(self.width, self.height)?;
             let pipe = self.grid[cross.pos]?;
             cross.traverse_pipe(pipe).map(|_| edge)
         });
@@ -296,11 +333,52 @@ impl Maze {
         }
     }
 
-    fn spaces_enclosed_by_loop(&self) -> u32 {
-        let mut visited = CornerVisitTracker::new();
+    /// The cells making up the loop, in order, by following it round from the start. If the loop
+    /// closes, the path finishes back at the start.
+    fn loop_path(&self) -> Vec<usize> {
+        let mut path = Vec::new();
+        let Some(start_pipe) = self.replacement_start_pipe() else {
+            return path;
+        };
+
+        path.push(self.start);
+        let mut pos = EdgePosition {
+            pos: self.start,
+            edge: start_pipe.0,
+            steps: 0,
+        }
+        .cross_edge(self.width, self.height);
+        while let Some(current) = pos {
+            path.push(current.pos);
+            if current.pos == self.start {
+                break;
+            }
+            pos = self.grid[current.pos]
+                .and_then(|pipe| current.traverse_pipe(pipe))
+                .and_then(|next| next.cross_edge(self.width, self.height));
+        }
+
+        path
+    }
+
+    /// Marks the cells which are part of the loop.
+    fn loop_cells(&self) -> Vec<bool> {
+        let mut cells = vec![false; self.grid.len()];
+        for pos in self.loop_path() {
+            cells[pos] = true;
+        }
+        cells
+    }
+
+    /// Flood-fills the gaps between pipes from the top-left corner; any cell with none of its
+    /// corners reached is enclosed by the loop. Only the pipes making up the loop block the fill,
+    /// so junk pipes elsewhere in the maze can't wall off part of the outside.
+    fn outside_corners(&self) -> CornerVisitTracker {
+        let mut visited = CornerVisitTracker::new(self.grid.len());
         let mut queue = VecDeque::new();
 
         let start_pipe = self.replacement_start_pipe();
+        let on_loop = self.loop_cells();
 
         queue.push_back(CornerPosition {
             pos: 0,
@@ -309,16 +387,69 @@ impl Maze {
 
         while let Some(pos) = queue.pop_front() {
             if !visited.visit(&pos) {
-                let pipe = if pos.pos == self.start {
+                let pipe = if !on_loop[pos.pos] {
+                    None
+                } else if pos.pos == self.start {
                     start_pipe
                 } else {
                     self.grid[pos.pos]
                 };
-                queue.extend(pos.adjacent_positions(pipe.as_ref()));
+                queue.extend(pos.adjacent_positions(pipe.as_ref(), self.width, self.height));
             }
         }
 
-        visited.count_unvisited()
+        visited
+    }
+
+    fn spaces_enclosed_by_loop(&self) -> u32 {
+        self.outside_corners().count_unvisited()
+    }
+
+    /// Counts the enclosed spaces from the loop alone, as the points strictly inside the
+    /// polygon through the centre of each cell on it (using the shoelace formula and Pick's
+    /// theorem). Gives `None` if there is no loop, or it doesn't lead back to the start.
+    fn spaces_enclosed_by_shoelace(&self) -> Option<u32> {
+        let path = self
+            .loop_path()
+            .into_iter()
+            .map(|pos| {
+                let row = i64::try_from(pos / self.width).ok()?;
+                let col = i64::try_from(pos % self.width).ok()?;
+                Some(Point::new(col, row))
+            })
+            .collect::<Option<Vec<Point>>>()?;
+        Polygon::from_closed_path(path)
+            .ok()?
+            .interior_points()
+            .ok()
+            .and_then(|spaces| u32::try_from(spaces).ok())
+    }
+
+    /// Draws the first `rows` by `cols` cells of the maze, with the loop traced out and the
+    /// spaces it encloses highlighted.
+    fn frame(&self, rows: usize, cols: usize) -> Frame {
+        let on_loop = self.loop_cells();
+        let outside = self.outside_corners();
+        let start_pipe = self.replacement_start_pipe();
+
+        Frame::from_fn(cols.min(self.width), rows.min(self.height), |row, col| {
+            let pos = self.position(row, col);
+            let pipe = if pos == self.start {
+                start_pipe
+            } else {
+                self.grid[pos]
+            };
+            match pipe {
+                Some(pipe) if on_loop[pos] => {
+                    Cell::new(pipe.symbol(), Style::fg(Colour::YELLOW).bold())
+                }
+                _ if outside.is_unvisited(pos) => {
+                    Cell::new('I', Style::fg(Colour::BLACK).on(Colour::GREEN))
+                }
+                Some(pipe) => Cell::new(pipe.symbol(), Style::fg(Colour::GREY)),
+                None => Cell::new('.', Style::fg(Colour::GREY)),
+            }
+        })
     }
 }
 
@@ -329,21 +460,29 @@ impl FromStr for Maze {
     type Err = ParseMazeError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut start = 0;
-        let mut grid = [None; GRID_SIZE * GRID_SIZE];
-
-        for (row, line) in input.lines().enumerate() {
-            let row_start = row * GRID_SIZE;
-            for (col, ch) in line.chars().enumerate() {
-                let pos = row_start + col;
-                grid[pos] = Pipe::read_from_char(ch);
+        let width = input.lines().next().map_or(0, |line| line.chars().count());
+        let mut start = None;
+        let mut grid = Vec::new();
+
+        for line in input.lines() {
+            if line.chars().count() != width {
+                return Err(ParseMazeError);
+            }
+            for ch in line.chars() {
                 if ch == 'S' {
-                    start = pos;
+                    start = Some(grid.len());
                 }
+                grid.push(Pipe::read_from_char(ch));
             }
         }
 
-        Ok(Self { start, grid })
+        let start = start.ok_or(ParseMazeError)?;
+        Ok(Self {
+            width,
+            height: grid.len() / width,
+            start,
+            grid,
+        })
     }
 }
 
@@ -357,12 +496,48 @@ pub fn part_two(input: &str) -> Option<u32> {
     Maze::from_str(input).map_or(None, |maze| Some(maze.spaces_enclosed_by_loop()))
 }
 
+/// With `--visualise`, draws the maze with the loop and the spaces inside it picked out. With
+/// `--export <file>`, saves the same picture as a `.png` or `.ppm` image. With `--shoelace`, also
+/// counts the enclosed spaces using the shoelace formula and Pick's theorem.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{flag_value, has_flag};
+
+    let export = flag_value("--export");
+    if !has_flag("--visualise") && !has_flag("--shoelace") && export.is_none() {
+        return;
+    }
+    let Ok(maze) = Maze::from_str(input) else {
+        eprintln!("Failed to read maze");
+        return;
+    };
+
+    if has_flag("--shoelace") {
+        match maze.spaces_enclosed_by_shoelace() {
+            Some(spaces) => println!("Part 2 by shoelace formula: {spaces}"),
+            None => println!("Part 2 by shoelace formula: no loop"),
+        }
+    }
+
+    let (rows, cols) = advent_of_code::render::dimensions(input);
+    let frame = maze.frame(rows, cols);
+    if has_flag("--visualise") {
+        print!("{frame}");
+    }
+    if let Some(path) = export {
+        image::export(&frame, &path);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// The width and height of the example maze.
+    const WIDTH: usize = 20;
+    const HEIGHT: usize = 11;
+
     fn position(row: usize, col: usize) -> usize {
-        (row * GRID_SIZE) + col
+        (row * WIDTH) + col
     }
 
     #[test]
@@ -403,6 +578,7 @@ mod tests {
         let maze: Maze = advent_of_code::template::read_file("examples", DAY)
             .parse()
             .expect("No error during Maze parsing");
+        assert_eq!((maze.width, maze.height), (WIDTH, HEIGHT));
         assert_eq!(maze.start, position(1, 4));
 
         let grid = maze.grid;
@@ -448,7 +624,7 @@ mod tests {
         };
         let pipe = Some(Pipe(Direction::East, Direction::South));
         assert_eq!(
-            pos.adjacent_positions(pipe.as_ref())
+            pos.adjacent_positions(pipe.as_ref(), WIDTH, HEIGHT)
                 .collect::<Vec<CornerPosition>>(),
             vec![
                 CornerPosition {
@@ -468,7 +644,7 @@ mod tests {
         };
         let pipe = Some(Pipe(Direction::East, Direction::West));
         assert_eq!(
-            pos.adjacent_positions(pipe.as_ref())
+            pos.adjacent_positions(pipe.as_ref(), WIDTH, HEIGHT)
                 .collect::<Vec<CornerPosition>>(),
             vec![
                 CornerPosition {
@@ -492,7 +668,7 @@ mod tests {
         };
         let pipe = None;
         assert_eq!(
-            pos.adjacent_positions(pipe.as_ref())
+            pos.adjacent_positions(pipe.as_ref(), WIDTH, HEIGHT)
                 .collect::<Vec<CornerPosition>>(),
             vec![
                 CornerPosition {
@@ -520,4 +696,119 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(10));
     }
+
+    #[test]
+    fn test_loop_cells() {
+        let maze: Maze = "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF".parse().unwrap();
+        let cells = maze.loop_cells();
+        assert_eq!(cells.iter().filter(|cell| **cell).count(), 8);
+        for (row, col) in [(1, 1), (1, 3), (3, 1), (3, 3), (2, 1), (2, 3)] {
+            assert!(cells[maze.position(row, col)], "({row}, {col}) on loop");
+        }
+        assert!(!cells[maze.position(2, 2)]);
+    }
+
+    #[test]
+    fn test_frame() {
+        let maze: Maze = "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF".parse().unwrap();
+        let frame = maze.frame(5, 5);
+        assert_eq!(frame.plain(), "─└│┌┐\n┐┌─┐│\n└│I││\n─└─┘│\n└│─┘┌\n");
+        assert_eq!(
+            frame.get(1, 1).unwrap().style,
+            Style::fg(Colour::YELLOW).bold()
+        );
+        assert_eq!(frame.get(0, 0).unwrap().style, Style::fg(Colour::GREY));
+    }
+
+    const SQUARE_LOOP: &str = ".....\n.S-7.\n.|.|.\n.L-J.\n.....\n";
+    const COMPLEX_LOOP: &str = "..F7.\n.FJ|.\nSJ.L7\n|F--J\nLJ...\n";
+    const ENCLOSED_LOOP: &str = concat!(
+        "...........\n",
+        ".S-------7.\n",
+        ".|F-----7|.\n",
+        ".||.....||.\n",
+        ".||.....||.\n",
+        ".|L-7.F-J|.\n",
+        ".|..|.|..|.\n",
+        ".L--J.L--J.\n",
+        "...........\n",
+    );
+    const SQUEEZED_LOOP: &str = concat!(
+        "..........\n",
+        ".S------7.\n",
+        ".|F----7|.\n",
+        ".||OOOO||.\n",
+        ".||OOOO||.\n",
+        ".|L-7F-J|.\n",
+        ".|II||II|.\n",
+        ".L--JL--J.\n",
+        "..........\n",
+    );
+    const LARGER_LOOP: &str = concat!(
+        ".F----7F7F7F7F-7....\n",
+        ".|F--7||||||||FJ....\n",
+        ".||.FJ||||||||L7....\n",
+        "FJL7L7LJLJ||LJ.L-7..\n",
+        "L--J.L7...LJS7F-7L7.\n",
+        "....F-J..F7FJ|L7L7L7\n",
+        "....L7.F7||L7|.L7L7|\n",
+        ".....|FJLJ|FJ|F7|.LJ\n",
+        "....FJL-7.||.||||...\n",
+        "....L---J.LJ.LJLJ...\n",
+    );
+
+    #[test]
+    fn test_smaller_mazes() {
+        assert_eq!(part_one(SQUARE_LOOP), Some(4));
+        assert_eq!(part_one(COMPLEX_LOOP), Some(8));
+        assert_eq!(part_two(ENCLOSED_LOOP), Some(4));
+        assert_eq!(part_two(SQUEEZED_LOOP), Some(4));
+        assert_eq!(part_two(LARGER_LOOP), Some(8));
+
+        let maze: Maze = COMPLEX_LOOP.parse().unwrap();
+        assert_eq!((maze.width, maze.height), (5, 5));
+        assert_eq!(maze.start, maze.position(2, 0));
+        let path = maze.loop_path();
+        assert_eq!(path.len(), 17);
+        assert_eq!(path.first(), path.last());
+    }
+
+    #[test]
+    fn test_parse_maze_errors() {
+        assert!(Maze::from_str("..F7.\n.FJ|\nSJ.L7").is_err());
+        assert!(Maze::from_str("..F7.\n.FJ|.\n.J.L7").is_err());
+        assert!(Maze::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_shoelace_matches_flood_fill() {
+        let example = advent_of_code::template::read_file("examples", DAY);
+        for input in [
+            SQUARE_LOOP,
+            COMPLEX_LOOP,
+            ENCLOSED_LOOP,
+            SQUEEZED_LOOP,
+            LARGER_LOOP,
+            &example,
+            "-L|F7\n7S-7|\nL|7||\n-L-J|\nL|-JF",
+            // junk pipes outside the loop mustn't wall off the corner the fill starts from.
+            "J....\n.S-7.\n.|.|.\n.L-J.\n.....",
+        ] {
+            let maze: Maze = input.parse().unwrap();
+            assert_eq!(
+                maze.spaces_enclosed_by_shoelace(),
+                Some(maze.spaces_enclosed_by_loop()),
+                "{input}"
+            );
+        }
+
+        // with no loop, there is nothing to enclose.
+        let maze: Maze = "S".parse().unwrap();
+        assert_eq!(maze.spaces_enclosed_by_loop(), 0);
+        assert_eq!(maze.spaces_enclosed_by_shoelace(), None);
+
+        // a loop which breaks off before getting back to the start isn't closed.
+        let maze: Maze = ".....\n.S-7.\n.|.|.\n.L-..\n.....\n".parse().unwrap();
+        assert_eq!(maze.spaces_enclosed_by_shoelace(), None);
+    }
 }
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
-advent_of_code::solution!(12);
+advent_of_code::solution!(12, explain);
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 enum Spring {
@@ -10,6 +10,52 @@ enum Spring {
     Unknown,
 }
 
+impl Spring {
+    const fn symbol(self) -> char {
+        match self {
+            Self::Operational => '.',
+            Self::Damaged => '#',
+            Self::Unknown => '?',
+        }
+    }
+}
+
+fn springs_to_string(springs: &[Spring]) -> String {
+    springs.iter().map(|spring| spring.symbol()).collect()
+}
+
+/// Iterates over every arrangement of a [`ConditionRecord`] in lexicographic order (with `#`
+/// before `.`, as in ASCII), sharing one table of counts between them.
+struct Arrangements<'a> {
+    record: &'a ConditionRecord,
+    table: ArrangementTable,
+    next: u64,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<Spring>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let arrangement = self.record.nth_arrangement_with(&self.table, self.next)?;
+        self.next += 1;
+        Some(arrangement)
+    }
+}
+
+/// The number of arrangements of each section of a [`ConditionRecord`]: the springs from
+/// `spring_ix` onwards, fitted to the groups from `group_ix` onwards. Stored flat, one row of
+/// groups per spring, with an extra row past the end for a group which fills the last springs.
+struct ArrangementTable {
+    row_len: usize,
+    counts: Vec<u64>,
+}
+
+impl ArrangementTable {
+    fn get(&self, spring_ix: usize, group_ix: usize) -> u64 {
+        self.counts[(spring_ix * self.row_len) + group_ix]
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct ConditionRecord {
     springs: Vec<Spring>,
@@ -18,6 +64,38 @@ struct ConditionRecord {
 
 impl ConditionRecord {
     fn possible_arrangements(&self) -> u64 {
+        self.arrangement_table().get(0, 0)
+    }
+
+    /// Fills in the counts for every section bottom-up, from the end of the springs backwards, so
+    /// that the sections each one depends on are always already known.
+    fn arrangement_table(&self) -> ArrangementTable {
+        let row_len = self.groups.len() + 1;
+        let mut table = ArrangementTable {
+            row_len,
+            counts: vec![0; (self.springs.len() + 2) * row_len],
+        };
+
+        for spring_ix in (0..=self.springs.len() + 1).rev() {
+            for group_ix in 0..row_len {
+                let consume_group = self.group_fits(spring_ix, group_ix).map_or(0, |group_len| {
+                    table.get(spring_ix + group_len + 1, group_ix + 1)
+                });
+                let skip = match self.springs.get(spring_ix) {
+                    None => u64::from(group_ix == self.groups.len()),
+                    Some(Spring::Damaged) => 0,
+                    Some(_) => table.get(spring_ix + 1, group_ix),
+                };
+                table.counts[(spring_ix * row_len) + group_ix] = consume_group + skip;
+            }
+        }
+
+        table
+    }
+
+    /// The original top-down count, recursing through the sections with a memo of those already
+    /// seen. Kept to check and benchmark [`Self::arrangement_table`] against.
+    fn possible_arrangements_recursive(&self) -> u64 {
         let mut cache = HashMap::new();
         self.possible_arrangements_for_section(&mut cache, 0, 0)
     }
@@ -33,25 +111,7 @@ impl ConditionRecord {
         }
 
         // check if the current group can be satisfied from this position:
-        let consume_group = self.groups.get(group_ix).map_or(0, |group_len| {
-            // group is long enough to fit within remaining springs
-            if (spring_ix + group_len) > self.springs.len() {
-                return 0;
-            }
-
-            // group does not contain Operational springs
-            if (0..*group_len)
-                .any(|pos| self.springs.get(spring_ix + pos) == Some(&Spring::Operational))
-            {
-                return 0;
-            }
-
-            // item after group is not a Damaged spring
-            if self.springs.get(spring_ix + group_len) == Some(&Spring::Damaged) {
-                return 0;
-            }
-
-            // if none of the above checks failed, we have a group which we can consume
+        let consume_group = self.group_fits(spring_ix, group_ix).map_or(0, |group_len| {
             self.possible_arrangements_for_section(cache, spring_ix + group_len + 1, group_ix + 1)
         });
 
@@ -68,13 +128,85 @@ impl ConditionRecord {
         arrangements
     }
 
-    fn unfold(&self) -> Self {
+    /// The length of the next group, if it can be placed starting at this position.
+    fn group_fits(&self, spring_ix: usize, group_ix: usize) -> Option<usize> {
+        let group_len = *self.groups.get(group_ix)?;
+
+        // group is long enough to fit within remaining springs
+        if (spring_ix + group_len) > self.springs.len() {
+            return None;
+        }
+
+        // group does not contain Operational springs
+        if (0..group_len).any(|pos| self.springs.get(spring_ix + pos) == Some(&Spring::Operational))
+        {
+            return None;
+        }
+
+        // item after group is not a Damaged spring
+        if self.springs.get(spring_ix + group_len) == Some(&Spring::Damaged) {
+            return None;
+        }
+
+        Some(group_len)
+    }
+
+    /// Every arrangement of the springs which fits the groups, in lexicographic order.
+    fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            record: self,
+            table: self.arrangement_table(),
+            next: 0,
+        }
+    }
+
+    /// The arrangement at index `n` in lexicographic order, if there are that many.
+    fn nth_arrangement(&self, n: u64) -> Option<Vec<Spring>> {
+        self.nth_arrangement_with(&self.arrangement_table(), n)
+    }
+
+    /// Finds the `n`th arrangement without listing the ones before it. At each position, the
+    /// arrangements which place the next group there come first (as they start with `#`); if
+    /// there are no more than `n` of them, they are skipped over and the position is left
+    /// operational instead.
+    fn nth_arrangement_with(&self, table: &ArrangementTable, mut n: u64) -> Option<Vec<Spring>> {
+        if n >= table.get(0, 0) {
+            return None;
+        }
+
+        let mut arrangement = Vec::with_capacity(self.springs.len());
+        let (mut spring_ix, mut group_ix) = (0, 0);
+
+        while spring_ix < self.springs.len() {
+            if let Some(group_len) = self.group_fits(spring_ix, group_ix) {
+                let next_ix = spring_ix + group_len + 1;
+                let consumed = table.get(next_ix, group_ix + 1);
+                if n < consumed {
+                    arrangement.extend((0..group_len).map(|_| Spring::Damaged));
+                    if next_ix <= self.springs.len() {
+                        arrangement.push(Spring::Operational);
+                    }
+                    (spring_ix, group_ix) = (next_ix, group_ix + 1);
+                    continue;
+                }
+                n -= consumed;
+            }
+
+            arrangement.push(Spring::Operational);
+            spring_ix += 1;
+        }
+
+        Some(arrangement)
+    }
+
+    /// Repeats the record `factor` times, joining the copies of the springs with unknown springs.
+    fn unfold(&self, factor: usize) -> Self {
         let mut springs = Vec::new();
         let mut groups = Vec::new();
 
-        for repeat in 1..=5 {
+        for repeat in 1..=factor {
             springs.extend(&self.springs);
-            if repeat != 5 {
+            if repeat != factor {
                 springs.push(Spring::Unknown);
             }
             groups.extend(&self.groups);
@@ -124,16 +256,14 @@ impl FromStr for ConditionRecord {
     }
 }
 
-fn total_possible_arrangements(input: &str, unfold: bool) -> u64 {
+const UNFOLD_FACTOR: usize = 5;
+
+fn total_possible_arrangements(input: &str, unfold_factor: usize) -> u64 {
     input
         .lines()
         .map(|line| {
             line.parse::<ConditionRecord>().map_or(0, |record| {
-                if unfold {
-                    record.unfold().possible_arrangements()
-                } else {
-                    record.possible_arrangements()
-                }
+                record.unfold(unfold_factor).possible_arrangements()
             })
         })
         .sum()
@@ -141,18 +271,88 @@ fn total_possible_arrangements(input: &str, unfold: bool) -> u64 {
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u64> {
-    Some(total_possible_arrangements(input, false))
+    Some(total_possible_arrangements(input, 1))
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u64> {
-    Some(total_possible_arrangements(input, true))
+    Some(total_possible_arrangements(input, UNFOLD_FACTOR))
+}
+
+/// With `--arrangements`, lists every arrangement of each record. With `--nth <n>`, shows the
+/// arrangement at index `n` (counting from 0, in lexicographic order) of each unfolded record.
+/// With `--bench`, compares the table of counts against the recursive memo on the unfolded
+/// records.
+fn explain(input: &str) {
+    use advent_of_code::template::runner::{benchmark, flag_value, has_flag};
+
+    if has_flag("--bench") {
+        let records: Vec<ConditionRecord> = input
+            .lines()
+            .filter_map(|line| line.parse::<ConditionRecord>().ok())
+            .map(|record| record.unfold(UNFOLD_FACTOR))
+            .collect();
+        benchmark(
+            "Recursive memo",
+            |records: &[ConditionRecord]| {
+                records
+                    .iter()
+                    .map(ConditionRecord::possible_arrangements_recursive)
+                    .sum::<u64>()
+            },
+            records.as_slice(),
+        );
+        benchmark(
+            "Table of counts",
+            |records: &[ConditionRecord]| {
+                records
+                    .iter()
+                    .map(ConditionRecord::possible_arrangements)
+                    .sum::<u64>()
+            },
+            records.as_slice(),
+        );
+    }
+
+    let nth = flag_value("--nth").and_then(|n| n.parse::<u64>().ok());
+    if !has_flag("--arrangements") && nth.is_none() {
+        return;
+    }
+
+    for line in input.lines() {
+        let Ok(record) = line.parse::<ConditionRecord>() else {
+            eprintln!("Failed to read record: {line}");
+            continue;
+        };
+        println!("{line}");
+        if has_flag("--arrangements") {
+            for arrangement in record.arrangements() {
+                println!("  {}", springs_to_string(&arrangement));
+            }
+        }
+        if let Some(n) = nth {
+            match record.unfold(UNFOLD_FACTOR).nth_arrangement(n) {
+                Some(arrangement) => println!("  #{n}: {}", springs_to_string(&arrangement)),
+                None => println!("  #{n}: no such arrangement"),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Checks both ways of counting arrangements agree on the expected count.
+    fn assert_possible_arrangements(record: &ConditionRecord, expected: u64) {
+        assert_eq!(record.possible_arrangements(), expected, "table");
+        assert_eq!(
+            record.possible_arrangements_recursive(),
+            expected,
+            "recursive"
+        );
+    }
+
     #[test]
     fn test_parse_condition_record() {
         assert_eq!(
@@ -220,7 +420,7 @@ mod tests {
             ],
             groups: vec![1, 1, 3],
         };
-        assert_eq!(record.possible_arrangements(), 1);
+        assert_possible_arrangements(&record, 1);
 
         let record = ConditionRecord {
             springs: vec![
@@ -242,7 +442,7 @@ mod tests {
             ],
             groups: vec![1, 3, 1, 6],
         };
-        assert_eq!(record.possible_arrangements(), 1);
+        assert_possible_arrangements(&record, 1);
     }
 
     #[test]
@@ -259,7 +459,7 @@ mod tests {
             ],
             groups: vec![1, 1, 3],
         };
-        assert_eq!(record.possible_arrangements(), 0);
+        assert_possible_arrangements(&record, 0);
 
         let record = ConditionRecord {
             springs: vec![
@@ -285,7 +485,7 @@ mod tests {
             ],
             groups: vec![],
         };
-        assert_eq!(record.possible_arrangements(), 0);
+        assert_possible_arrangements(&record, 0);
     }
 
     #[test]
@@ -309,7 +509,7 @@ mod tests {
             ],
             groups: vec![1, 1, 3],
         };
-        assert_eq!(record.possible_arrangements(), 4);
+        assert_possible_arrangements(&record, 4);
 
         let record = ConditionRecord {
             springs: vec![
@@ -328,7 +528,7 @@ mod tests {
             ],
             groups: vec![3, 2, 1],
         };
-        assert_eq!(record.possible_arrangements(), 10);
+        assert_possible_arrangements(&record, 10);
     }
 
     #[test]
@@ -362,7 +562,28 @@ mod tests {
             ],
             groups: vec![1, 1, 1, 1, 1],
         };
-        assert_eq!(record.unfold(), unfolded);
+        assert_eq!(record.unfold(5), unfolded);
+        assert_eq!(record.unfold(1), record);
+    }
+
+    #[test]
+    fn test_possible_arrangements_unfolded() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let records: Vec<ConditionRecord> =
+            input.lines().map(|line| line.parse().unwrap()).collect();
+
+        for (record, expected) in records.iter().zip([1, 4, 1, 1, 4, 10]) {
+            assert_possible_arrangements(record, expected);
+        }
+        for (record, expected) in records.iter().zip([1, 16_384, 1, 16, 2_500, 506_250]) {
+            assert_possible_arrangements(&record.unfold(5), expected);
+        }
+        for record in &records {
+            for factor in [2, 3, 7] {
+                let unfolded = record.unfold(factor);
+                assert_possible_arrangements(&unfolded, unfolded.possible_arrangements_recursive());
+            }
+        }
     }
 
     #[test]
@@ -370,4 +591,114 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(525_152));
     }
+
+    /// Checks the arrangement has no unknown springs, agrees with the record wherever the
+    /// record is known, and has the right groups of damaged springs.
+    fn fits_record(record: &ConditionRecord, arrangement: &[Spring]) -> bool {
+        let groups: Vec<usize> = springs_to_string(arrangement)
+            .split('.')
+            .filter(|group| !group.is_empty())
+            .map(str::len)
+            .collect();
+        arrangement.len() == record.springs.len()
+            && arrangement
+                .iter()
+                .zip(&record.springs)
+                .all(|(spring, known)| {
+                    *spring != Spring::Unknown && (known == spring || *known == Spring::Unknown)
+                })
+            && groups == record.groups
+    }
+
+    /// Lists the arrangements by trying every combination of the unknown springs.
+    fn brute_force_arrangements(record: &ConditionRecord) -> Vec<String> {
+        let unknowns = record
+            .springs
+            .iter()
+            .filter(|spring| **spring == Spring::Unknown)
+            .count();
+        let mut arrangements: Vec<String> = (0..1_u32 << unknowns)
+            .map(|mut choices| {
+                record
+                    .springs
+                    .iter()
+                    .map(|spring| match spring {
+                        Spring::Unknown => {
+                            let damaged = choices & 1 == 1;
+                            choices >>= 1;
+                            if damaged {
+                                Spring::Damaged
+                            } else {
+                                Spring::Operational
+                            }
+                        }
+                        known => *known,
+                    })
+                    .collect::<Vec<Spring>>()
+            })
+            .filter(|arrangement| fits_record(record, arrangement))
+            .map(|arrangement| springs_to_string(&arrangement))
+            .collect();
+        arrangements.sort_unstable();
+        arrangements
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let record: ConditionRecord = ".??..??...?##. 1,1,3".parse().unwrap();
+        let arrangements: Vec<String> = record
+            .arrangements()
+            .map(|arrangement| springs_to_string(&arrangement))
+            .collect();
+        assert_eq!(
+            arrangements,
+            vec![
+                ".#...#....###.",
+                ".#....#...###.",
+                "..#..#....###.",
+                "..#...#...###.",
+            ]
+        );
+
+        let input = advent_of_code::template::read_file("examples", DAY);
+        for line in input.lines() {
+            let record: ConditionRecord = line.parse().unwrap();
+            let arrangements: Vec<String> = record
+                .arrangements()
+                .map(|arrangement| springs_to_string(&arrangement))
+                .collect();
+            assert_eq!(arrangements, brute_force_arrangements(&record), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_nth_arrangement() {
+        let record: ConditionRecord = "?###???????? 3,2,1".parse().unwrap();
+        let all: Vec<Vec<Spring>> = record.arrangements().collect();
+        assert_eq!(all.len(), 10);
+        for (n, arrangement) in all.iter().enumerate() {
+            assert_eq!(record.nth_arrangement(n as u64).as_ref(), Some(arrangement));
+        }
+        assert_eq!(record.nth_arrangement(10), None);
+
+        // the unfolded record has too many arrangements to list, but any one can be found.
+        let unfolded = record.unfold(UNFOLD_FACTOR);
+        let count = unfolded.possible_arrangements();
+        assert_eq!(count, unfolded.possible_arrangements_recursive());
+        assert_eq!(count, 506_250);
+        for n in [0, 1, 12_345, count / 2, count - 1] {
+            let arrangement = unfolded.nth_arrangement(n).unwrap();
+            assert!(fits_record(&unfolded, &arrangement), "{n}");
+        }
+        assert_eq!(unfolded.nth_arrangement(count), None);
+
+        let first = unfolded.nth_arrangement(0).unwrap();
+        let last = unfolded.nth_arrangement(count - 1).unwrap();
+        assert!(springs_to_string(&first) < springs_to_string(&last));
+        assert_eq!(unfolded.arrangements().next(), Some(first));
+        assert_eq!(
+            unfolded.arrangements().nth(12_345),
+            unfolded.nth_arrangement(12_345)
+        );
+    }
 }
@@ -1,21 +1,30 @@
+use advent_of_code::render::{Cell, Colour, Frame, Style};
 use std::str::FromStr;
 
-advent_of_code::solution!(13);
+advent_of_code::solution!(13, extras);
 
+/// Finds each position which reflects the sequence with exactly `smudges` differing bits, along
+/// with those differences as (index, bit) pairs, taking the index from the near side of the
+/// reflection.
 fn mirrors_in_sequence(
     sequence: &[usize],
-    acceptable_deviation: u32,
-) -> impl Iterator<Item = usize> + '_ {
-    (1..sequence.len()).filter(move |ix| {
-        let mut left: Vec<&usize> = sequence[..*ix].iter().collect();
-        left.reverse();
-        let right = &sequence[*ix..];
-        let size = left.len().min(right.len());
-
-        (0..size)
-            .fold(0, |acc, pos| acc | (left[pos] ^ right[pos]))
-            .count_ones()
-            == acceptable_deviation
+    smudges: usize,
+) -> impl Iterator<Item = (usize, Vec<(usize, usize)>)> + '_ {
+    (1..sequence.len()).filter_map(move |ix| {
+        let mut differences = Vec::new();
+
+        for (near, far) in (0..ix).rev().zip(ix..sequence.len()) {
+            let mut diff = sequence[near] ^ sequence[far];
+            while diff != 0 {
+                differences.push((near, diff.trailing_zeros() as usize));
+                if differences.len() > smudges {
+                    return None;
+                }
+                diff &= diff - 1;
+            }
+        }
+
+        (differences.len() == smudges).then_some((ix, differences))
     })
 }
 
@@ -34,6 +43,14 @@ impl Mirror {
     }
 }
 
+/// A mirror, and the (row, column) of each smudged cell which must change for the pattern to
+/// reflect in it exactly. Each smudge is given on the left of or above the mirror.
+#[derive(Debug, PartialEq)]
+struct Reflection {
+    mirror: Mirror,
+    smudges: Vec<(usize, usize)>,
+}
+
 #[derive(Debug, PartialEq)]
 struct Pattern {
     cols: Vec<usize>,
@@ -41,12 +58,70 @@ struct Pattern {
 }
 
 impl Pattern {
-    fn find_mirrors(&self, smudge: bool) -> impl Iterator<Item = Mirror> + '_ {
-        let acceptable_variance = u32::from(smudge);
-        let vert = mirrors_in_sequence(&self.cols, acceptable_variance).map(Mirror::Vertical);
-        let horz = mirrors_in_sequence(&self.rows, acceptable_variance).map(Mirror::Horizontal);
+    fn find_mirrors(&self, smudges: usize) -> impl Iterator<Item = Reflection> + '_ {
+        let vert = mirrors_in_sequence(&self.cols, smudges).map(|(cols, differences)| Reflection {
+            mirror: Mirror::Vertical(cols),
+            smudges: differences
+                .into_iter()
+                .map(|(col, row)| (row, col))
+                .collect(),
+        });
+        let horz = mirrors_in_sequence(&self.rows, smudges).map(|(rows, smudges)| Reflection {
+            mirror: Mirror::Horizontal(rows),
+            smudges,
+        });
         vert.chain(horz)
     }
+
+    fn is_rock(&self, row: usize, col: usize) -> bool {
+        self.rows[row] & (1 << col) != 0
+    }
+
+    /// Draws the pattern with the mirror as an extra line between the rows or columns it
+    /// separates, and the smudged cells highlighted. Cells with no reflection are dimmed.
+    fn frame(&self, reflection: &Reflection) -> Frame {
+        let (height, width) = (self.rows.len(), self.cols.len());
+        let (line_row, line_col) = match reflection.mirror {
+            Mirror::Horizontal(rows) => (Some(rows), None),
+            Mirror::Vertical(cols) => (None, Some(cols)),
+        };
+        let mirror_style = Style::fg(Colour::CYAN).bold();
+
+        Frame::from_fn(
+            width + usize::from(line_col.is_some()),
+            height + usize::from(line_row.is_some()),
+            |row, col| {
+                if Some(row) == line_row {
+                    return Cell::new('-', mirror_style);
+                }
+                if Some(col) == line_col {
+                    return Cell::new('|', mirror_style);
+                }
+                let row = row - usize::from(line_row.is_some_and(|line| row > line));
+                let col = col - usize::from(line_col.is_some_and(|line| col > line));
+
+                let symbol = if self.is_rock(row, col) { '#' } else { '.' };
+                let reflected = match reflection.mirror {
+                    Mirror::Horizontal(rows) => {
+                        let span = rows.min(height - rows);
+                        (rows - span..rows + span).contains(&row)
+                    }
+                    Mirror::Vertical(cols) => {
+                        let span = cols.min(width - cols);
+                        (cols - span..cols + span).contains(&col)
+                    }
+                };
+                let style = if reflection.smudges.contains(&(row, col)) {
+                    Style::fg(Colour::WHITE).on(Colour::RED).bold()
+                } else if reflected {
+                    Style::PLAIN
+                } else {
+                    Style::fg(Colour::GREY)
+                };
+                Cell::new(symbol, style)
+            },
+        )
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -84,15 +159,15 @@ impl FromStr for Pattern {
     }
 }
 
-fn total_mirror_value(input: &str, smudge: bool) -> Option<usize> {
+fn total_mirror_value(input: &str, smudges: usize) -> Option<usize> {
     let (errors, total) = input.split("\n\n").fold((0, 0), |(errors, total), line| {
         let Ok(pattern) = Pattern::from_str(line) else {
             return (errors + 1, total);
         };
-        let Some(mirror) = pattern.find_mirrors(smudge).next() else {
+        let Some(reflection) = pattern.find_mirrors(smudges).next() else {
             return (errors + 1, total);
         };
-        (errors, total + mirror.value())
+        (errors, total + reflection.mirror.value())
     });
     if errors > 0 {
         None
@@ -103,12 +178,52 @@ fn total_mirror_value(input: &str, smudge: bool) -> Option<usize> {
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    total_mirror_value(input, false)
+    total_mirror_value(input, 0)
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    total_mirror_value(input, true)
+    total_mirror_value(input, 1)
+}
+
+/// With `--visualise`, draws each pattern with its mirror and smudges: for both parts, or with
+/// `--smudges <n>` for mirrors which need exactly `n` cells to change.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{flag_value, has_flag};
+
+    if !has_flag("--visualise") {
+        return;
+    }
+    let smudge_counts = match flag_value("--smudges").map(|n| n.parse::<usize>()) {
+        None => vec![0, 1],
+        Some(Ok(smudges)) => vec![smudges],
+        Some(Err(_)) => {
+            eprintln!("--smudges must be a number");
+            return;
+        }
+    };
+
+    for (ix, block) in input.split("\n\n").enumerate() {
+        let Ok(pattern) = Pattern::from_str(block) else {
+            eprintln!("Failed to read pattern {}", ix + 1);
+            continue;
+        };
+        for &smudges in &smudge_counts {
+            print!("Pattern {} with {smudges} smudges: ", ix + 1);
+            let Some(reflection) = pattern.find_mirrors(smudges).next() else {
+                println!("no mirror");
+                continue;
+            };
+            match reflection.mirror {
+                Mirror::Horizontal(rows) => println!("mirror below row {rows}"),
+                Mirror::Vertical(cols) => println!("mirror right of column {cols}"),
+            }
+            for (row, col) in &reflection.smudges {
+                println!("  smudge at row {}, column {}", row + 1, col + 1);
+            }
+            print!("{}", pattern.frame(&reflection));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -152,32 +267,57 @@ mod tests {
     #[test]
     fn test_mirrors_in_sequence() {
         assert_eq!(
-            mirrors_in_sequence(&[13, 5, 5, 13], 0).collect::<Vec<usize>>(),
-            vec![2]
+            mirrors_in_sequence(&[13, 5, 5, 13], 0).collect::<Vec<_>>(),
+            vec![(2, vec![])]
+        );
+        assert_eq!(
+            mirrors_in_sequence(&[7, 1, 7, 7, 1, 7], 0).collect::<Vec<_>>(),
+            vec![(3, vec![])]
         );
         assert_eq!(
-            mirrors_in_sequence(&[7, 1, 7, 7, 1, 7], 0).collect::<Vec<usize>>(),
-            vec![3]
+            mirrors_in_sequence(&[13, 14, 15, 16], 0).collect::<Vec<_>>(),
+            Vec::new()
         );
         assert_eq!(
-            mirrors_in_sequence(&[13, 14, 15, 16], 0).collect::<Vec<usize>>(),
+            mirrors_in_sequence(&[13, 5, 5, 14], 0).collect::<Vec<_>>(),
             Vec::new()
         );
         assert_eq!(
-            mirrors_in_sequence(&[13, 5, 5, 14], 0).collect::<Vec<usize>>(),
+            mirrors_in_sequence(&[13, 5, 5, 14], 2).collect::<Vec<_>>(),
+            vec![(2, vec![(0, 0), (0, 1)])]
+        );
+        // differences in the same bit of different pairs count separately.
+        assert_eq!(
+            mirrors_in_sequence(&[1, 1, 0, 0], 1).collect::<Vec<_>>(),
             Vec::new()
         );
+        assert_eq!(
+            mirrors_in_sequence(&[1, 1, 0, 0], 2).collect::<Vec<_>>(),
+            vec![(2, vec![(1, 0), (0, 0)])]
+        );
     }
 
     #[test]
     fn test_pattern_find_mirrors() {
         let pattern = first_example_pattern();
-        let mirrors: Vec<Mirror> = pattern.find_mirrors(false).collect();
-        assert_eq!(mirrors, vec![Mirror::Vertical(5)]);
+        let mirrors: Vec<Reflection> = pattern.find_mirrors(0).collect();
+        assert_eq!(
+            mirrors,
+            vec![Reflection {
+                mirror: Mirror::Vertical(5),
+                smudges: vec![]
+            }]
+        );
 
         let pattern = second_example_pattern();
-        let mirrors: Vec<Mirror> = pattern.find_mirrors(false).collect();
-        assert_eq!(mirrors, vec![Mirror::Horizontal(4)]);
+        let mirrors: Vec<Reflection> = pattern.find_mirrors(0).collect();
+        assert_eq!(
+            mirrors,
+            vec![Reflection {
+                mirror: Mirror::Horizontal(4),
+                smudges: vec![]
+            }]
+        );
     }
 
     #[test]
@@ -189,12 +329,91 @@ mod tests {
     #[test]
     fn test_pattern_find_mirrors_with_smudge() {
         let pattern = first_example_pattern();
-        let mirrors: Vec<Mirror> = pattern.find_mirrors(true).collect();
-        assert_eq!(mirrors, vec![Mirror::Horizontal(3)]);
+        let mirrors: Vec<Reflection> = pattern.find_mirrors(1).collect();
+        assert_eq!(
+            mirrors,
+            vec![Reflection {
+                mirror: Mirror::Horizontal(3),
+                smudges: vec![(0, 0)]
+            }]
+        );
 
         let pattern = second_example_pattern();
-        let mirrors: Vec<Mirror> = pattern.find_mirrors(true).collect();
-        assert_eq!(mirrors, vec![Mirror::Horizontal(1)]);
+        let mirrors: Vec<Reflection> = pattern.find_mirrors(1).collect();
+        assert_eq!(
+            mirrors,
+            vec![Reflection {
+                mirror: Mirror::Horizontal(1),
+                smudges: vec![(0, 4)]
+            }]
+        );
+    }
+
+    fn with_cells_flipped(pattern: &Pattern, cells: &[(usize, usize)]) -> Pattern {
+        let mut rows = pattern.rows.clone();
+        let mut cols = pattern.cols.clone();
+        for (row, col) in cells {
+            rows[*row] ^= 1 << col;
+            cols[*col] ^= 1 << row;
+        }
+        Pattern { cols, rows }
+    }
+
+    #[test]
+    fn test_pattern_find_mirrors_with_smudges() {
+        for pattern in [first_example_pattern(), second_example_pattern()] {
+            for smudges in 0..=4 {
+                for reflection in pattern.find_mirrors(smudges) {
+                    assert_eq!(reflection.smudges.len(), smudges);
+                    let cleaned = with_cells_flipped(&pattern, &reflection.smudges);
+                    assert!(cleaned
+                        .find_mirrors(0)
+                        .any(|clean| clean.mirror == reflection.mirror));
+                }
+            }
+        }
+
+        let pattern = first_example_pattern();
+        let mirrors: Vec<Reflection> = pattern.find_mirrors(2).collect();
+        assert_eq!(
+            mirrors,
+            vec![Reflection {
+                mirror: Mirror::Vertical(1),
+                smudges: vec![(0, 0), (6, 0)]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_pattern_frame() {
+        let pattern = first_example_pattern();
+        let reflection = pattern.find_mirrors(0).next().unwrap();
+        let frame = pattern.frame(&reflection);
+        assert_eq!(
+            frame.plain(),
+            [
+                "#.##.|.##.\n",
+                "..#.#|#.#.\n",
+                "##...|...#\n",
+                "##...|...#\n",
+                "..#.#|#.#.\n",
+                "..##.|.##.\n",
+                "#.#.#|#.#.\n",
+            ]
+            .concat()
+        );
+        assert_eq!(frame.get(0, 0).unwrap().style, Style::fg(Colour::GREY));
+        assert_eq!(frame.get(0, 1).unwrap().style, Style::PLAIN);
+
+        let reflection = pattern.find_mirrors(1).next().unwrap();
+        let frame = pattern.frame(&reflection);
+        assert_eq!(frame.height(), 8);
+        assert_eq!(frame.get(3, 0).unwrap().symbol, '-');
+        assert_eq!(
+            frame.get(0, 0).unwrap().style,
+            Style::fg(Colour::WHITE).on(Colour::RED).bold()
+        );
+        assert_eq!(frame.get(7, 0).unwrap().style, Style::fg(Colour::GREY));
     }
 
     #[test]
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use advent_of_code::cycle::{self, History};
+use advent_of_code::image;
+use advent_of_code::render::{self, Cell, Colour, Frame, Style};
 use std::str::FromStr;
+use std::time::Duration;
 
-advent_of_code::solution!(14);
+advent_of_code::solution!(14, extras);
 
 const GRID_SIZE: usize = 100;
 
@@ -18,26 +21,15 @@ struct Dish {
 }
 
 impl Dish {
-    fn load_after_cycles(self, cycles: u32) -> usize {
-        let mut visited = HashMap::new();
-        let mut state = self;
-
-        for cycle in 1..=cycles {
-            state = state.tilt_cycle();
-
-            if let Some(first_visited) = visited.get(&state) {
-                let repeat_length = cycle - first_visited;
-                let remaining = (cycles - cycle) % repeat_length;
-                for _ in 0..remaining {
-                    state = state.tilt_cycle();
-                }
-                break;
-            }
-
-            visited.insert(state, cycle);
-        }
+    /// The states seen while repeatedly running spin cycles, up to the first repeat.
+    fn spin_history(self) -> History<Self> {
+        cycle::hashed(self, Self::tilt_cycle)
+    }
 
-        state.load_on_north_support_beams()
+    fn load_after_cycles(self, cycles: usize) -> usize {
+        self.spin_history()
+            .state_at(cycles)
+            .load_on_north_support_beams()
     }
 
     fn load_on_north_support_beams(&self) -> usize {
@@ -149,6 +141,170 @@ impl Dish {
     fn tilt_cycle(&self) -> Self {
         self.roll_north().roll_west().roll_south().roll_east()
     }
+
+    /// Frames showing each tilt of the first spin cycle, then the dish after each spin cycle
+    /// until it starts repeating.
+    fn spin_frames(self, rows: usize, cols: usize) -> Vec<Frame> {
+        let tilts = [Self::roll_north, Self::roll_west, Self::roll_south]
+            .into_iter()
+            .scan(self, |dish, roll| {
+                *dish = roll(dish);
+                Some(dish.frame(rows, cols))
+            });
+
+        let history = self.spin_history();
+        let cycle = history.cycle;
+        let cycles =
+            (1..=cycle.prefix + cycle.period).map(|n| history.state_at(n).frame(rows, cols));
+
+        std::iter::once(self.frame(rows, cols))
+            .chain(tilts)
+            .chain(cycles)
+            .collect()
+    }
+
+    /// Draws the top-left `rows` by `cols` part of the dish.
+    fn frame(&self, rows: usize, cols: usize) -> Frame {
+        Frame::from_fn(cols, rows, |row, col| {
+            match self.grid[(row * GRID_SIZE) + col] {
+                Rock::Empty => Cell::new('.', Style::fg(Colour::GREY)),
+                Rock::Rounded => Cell::new('O', Style::fg(Colour::ORANGE).bold()),
+                Rock::Cube => Cell::new('#', Style::fg(Colour::BLUE)),
+            }
+        })
+    }
+}
+
+/// One bit per cell (with bit `n` for column `n`) and one `u128` per row.
+type Rows = [u128; GRID_SIZE];
+
+/// A bit-packed [`Dish`]: the cube-shaped rocks never move, so only the rounded rocks make up
+/// the state which changes (and is hashed) from one spin cycle to the next.
+#[derive(Debug, PartialEq)]
+struct PackedDish {
+    cubes: Rows,
+    rounded: Rows,
+}
+
+/// The bits from `start` (inclusive) to `end` (exclusive).
+const fn bit_range(start: u32, end: u32) -> u128 {
+    if start >= end {
+        0
+    } else {
+        (u128::MAX >> (128 - (end - start))) << start
+    }
+}
+
+/// Rolls the rounded rocks in one row as far as they go, towards column zero if `west` is set
+/// and away from it otherwise. Each gap between the cubes ends up with the same number of
+/// rocks, packed against one side of the gap.
+const fn roll_row(rounded: u128, cubes: u128, west: bool) -> u128 {
+    #[allow(clippy::cast_possible_truncation)]
+    const WIDTH: u32 = GRID_SIZE as u32;
+    let walls = cubes | (1 << WIDTH);
+
+    let mut rolled = 0;
+    let mut start = 0;
+    while start < WIDTH {
+        let end = start + (walls >> start).trailing_zeros();
+        let count = (rounded & bit_range(start, end)).count_ones();
+        rolled |= if west {
+            bit_range(start, start + count)
+        } else {
+            bit_range(end - count, end)
+        };
+        start = end + 1;
+    }
+    rolled
+}
+
+impl PackedDish {
+    fn load(rounded: &Rows) -> usize {
+        rounded
+            .iter()
+            .enumerate()
+            .map(|(row, bits)| (GRID_SIZE - row) * bits.count_ones() as usize)
+            .sum()
+    }
+
+    /// Moves rocks up one row wherever the space above is free, repeating until none can move.
+    /// Working from the bottom up lets a rock travel the whole way in one sweep unless it was
+    /// waiting for the rock in front of it.
+    fn roll_north(&self, rounded: &Rows) -> Rows {
+        let mut rows = *rounded;
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for row in (1..GRID_SIZE).rev() {
+                let rolling = rows[row] & !(rows[row - 1] | self.cubes[row - 1]);
+                if rolling != 0 {
+                    rows[row] &= !rolling;
+                    rows[row - 1] |= rolling;
+                    moved = true;
+                }
+            }
+        }
+        rows
+    }
+
+    fn roll_south(&self, rounded: &Rows) -> Rows {
+        let mut rows = *rounded;
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for row in 0..(GRID_SIZE - 1) {
+                let rolling = rows[row] & !(rows[row + 1] | self.cubes[row + 1]);
+                if rolling != 0 {
+                    rows[row] &= !rolling;
+                    rows[row + 1] |= rolling;
+                    moved = true;
+                }
+            }
+        }
+        rows
+    }
+
+    fn roll_sideways(&self, rounded: &Rows, west: bool) -> Rows {
+        let mut rows = [0; GRID_SIZE];
+        for (row, rolled) in rows.iter_mut().enumerate() {
+            *rolled = roll_row(rounded[row], self.cubes[row], west);
+        }
+        rows
+    }
+
+    fn tilt_cycle(&self, rounded: &Rows) -> Rows {
+        let rounded = self.roll_north(rounded);
+        let rounded = self.roll_sideways(&rounded, true);
+        let rounded = self.roll_south(&rounded);
+        self.roll_sideways(&rounded, false)
+    }
+
+    /// The states seen while repeatedly running spin cycles, up to the first repeat.
+    fn spin_history(&self) -> History<Rows> {
+        cycle::hashed(self.rounded, |rounded| self.tilt_cycle(rounded))
+    }
+
+    fn load_after_cycles(&self, cycles: usize) -> usize {
+        Self::load(self.spin_history().state_at(cycles))
+    }
+}
+
+impl From<&Dish> for PackedDish {
+    fn from(dish: &Dish) -> Self {
+        let mut cubes = [0; GRID_SIZE];
+        let mut rounded = [0; GRID_SIZE];
+
+        for (pos, rock) in dish.grid.iter().enumerate() {
+            let bit = 1 << (pos % GRID_SIZE);
+            match rock {
+                Rock::Empty => (),
+                Rock::Cube => cubes[pos / GRID_SIZE] |= bit,
+                Rock::Rounded => rounded[pos / GRID_SIZE] |= bit,
+            }
+        }
+
+        Self { cubes, rounded }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -184,16 +340,79 @@ impl FromStr for Dish {
     }
 }
 
+fn read_packed_dish(input: &str) -> Result<PackedDish, ParseDishError> {
+    Dish::from_str(input).map(|dish| PackedDish::from(&dish))
+}
+
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
-    Dish::from_str(input).map_or(None, |dish| {
-        Some(dish.roll_north().load_on_north_support_beams())
+    read_packed_dish(input).map_or(None, |dish| {
+        Some(PackedDish::load(&dish.roll_north(&dish.rounded)))
     })
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    Dish::from_str(input).map_or(None, |dish| Some(dish.load_after_cycles(1_000_000_000)))
+    read_packed_dish(input).map_or(None, |dish| Some(dish.load_after_cycles(1_000_000_000)))
+}
+
+/// With `--cycle`, prints where the spin cycles start repeating. With `--bench`, compares the
+/// bit-packed spin cycles against the original cell-by-cell ones. With `--visualise`, animates
+/// each tilt of the first spin cycle, then each spin cycle until the dish starts repeating, and
+/// with `--frames <directory>` saves those same frames there as numbered images.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{benchmark, flag_value, has_flag};
+
+    let Ok(dish) = Dish::from_str(input) else {
+        if has_flag("--cycle")
+            || has_flag("--bench")
+            || has_flag("--visualise")
+            || has_flag("--frames")
+        {
+            eprintln!("Failed to read dish");
+        }
+        return;
+    };
+    let packed = PackedDish::from(&dish);
+
+    if has_flag("--cycle") {
+        let cycle = packed.spin_history().cycle;
+        println!(
+            "Spin cycles repeat every {} cycles, after the first {}.",
+            cycle.period, cycle.prefix
+        );
+    }
+
+    if has_flag("--bench") {
+        benchmark("Grid spin cycle", |dish: Dish| dish.tilt_cycle(), dish);
+        benchmark(
+            "Packed spin cycle",
+            |packed: &PackedDish| packed.tilt_cycle(&packed.rounded),
+            &packed,
+        );
+        benchmark(
+            "Grid load after cycles",
+            |dish: Dish| dish.load_after_cycles(1_000_000_000),
+            dish,
+        );
+        benchmark(
+            "Packed load after cycles",
+            |packed: &PackedDish| packed.load_after_cycles(1_000_000_000),
+            &packed,
+        );
+    }
+
+    let frames_directory = flag_value("--frames");
+    if has_flag("--visualise") || frames_directory.is_some() {
+        let (rows, cols) = render::dimensions(input);
+        let frames = dish.spin_frames(rows, cols);
+        if let Some(directory) = frames_directory {
+            image::export_sequence(frames.clone(), &directory);
+        }
+        if has_flag("--visualise") {
+            render::animate(frames, Duration::from_millis(150));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -306,12 +525,84 @@ mod tests {
         assert_eq!(example_rolled_north().load_on_north_support_beams(), 1756);
     }
 
+    #[test]
+    fn test_roll_row() {
+        // cubes in columns 4 and 9 split the row into three gaps.
+        let cubes = 0b10_0001_0000;
+        let rounded = 0b1001_0100_1011;
+        assert_eq!(roll_row(rounded, cubes, true), 0b0100_0110_0111);
+        assert_eq!(
+            roll_row(rounded, cubes, false),
+            (1 << (GRID_SIZE - 1)) | 0b1_1000_0000 | 0b1110
+        );
+    }
+
+    #[test]
+    fn test_packed_matches_grid() {
+        let dish = example();
+        let packed = PackedDish::from(&dish);
+
+        assert_eq!(
+            packed.roll_north(&packed.rounded),
+            PackedDish::from(&dish.roll_north()).rounded
+        );
+        assert_eq!(
+            packed.roll_south(&packed.rounded),
+            PackedDish::from(&dish.roll_south()).rounded
+        );
+        assert_eq!(
+            packed.roll_sideways(&packed.rounded, true),
+            PackedDish::from(&dish.roll_west()).rounded
+        );
+        assert_eq!(
+            packed.roll_sideways(&packed.rounded, false),
+            PackedDish::from(&dish.roll_east()).rounded
+        );
+
+        let mut grid_state = dish;
+        let mut packed_state = packed.rounded;
+        for _ in 0..5 {
+            grid_state = grid_state.tilt_cycle();
+            packed_state = packed.tilt_cycle(&packed_state);
+            assert_eq!(packed_state, PackedDish::from(&grid_state).rounded);
+        }
+        assert_eq!(packed.spin_history().cycle, dish.spin_history().cycle);
+        assert_eq!(
+            packed.load_after_cycles(1_000_000_000),
+            dish.load_after_cycles(1_000_000_000)
+        );
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(1756));
     }
 
+    #[test]
+    fn test_spin_cycle() {
+        let cycle = example().spin_history().cycle;
+        assert_eq!(cycle, cycle::brent(&example(), Dish::tilt_cycle));
+        assert_eq!(cycle, cycle::floyd(&example(), Dish::tilt_cycle));
+    }
+
+    #[test]
+    fn test_frame() {
+        let frame = example_rolled_north().frame(3, 10);
+        assert_eq!(frame.plain(), "OOOO.#.O..\nOO..#....#\nOO..O##..O\n");
+        assert_eq!(frame.get(0, 5).unwrap().style, Style::fg(Colour::BLUE));
+    }
+
+    #[test]
+    fn test_spin_frames() {
+        let frames = example().spin_frames(10, 10);
+        let cycle = example().spin_history().cycle;
+        assert_eq!(frames.len(), 4 + cycle.prefix + cycle.period);
+        assert_eq!(frames[0], example().frame(10, 10));
+        assert_eq!(frames[1], example_rolled_north().frame(10, 10));
+        assert_eq!(frames[4], example().tilt_cycle().frame(10, 10));
+    }
+
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
@@ -1,9 +1,8 @@
-advent_of_code::solution!(15);
+use advent_of_code::hash::hash;
+use std::fmt;
+use std::str::FromStr;
 
-fn hash(step: &str) -> usize {
-    step.chars()
-        .fold(0, |acc, ch| (acc + (ch as usize)) * 17 % 256)
-}
+advent_of_code::solution!(15, replay);
 
 #[derive(Debug, PartialEq)]
 struct Lens {
@@ -11,6 +10,42 @@ struct Lens {
     focal_length: usize,
 }
 
+#[derive(Debug, PartialEq)]
+enum Step {
+    Remove(String),
+    Insert(Lens),
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseStepError;
+
+impl FromStr for Step {
+    type Err = ParseStepError;
+
+    fn from_str(step: &str) -> Result<Self, Self::Err> {
+        if let Some(label) = step.strip_suffix('-') {
+            if label.is_empty() {
+                return Err(ParseStepError);
+            }
+            return Ok(Self::Remove(label.to_string()));
+        }
+
+        let (label, focal_length) = step.split_once('=').ok_or(ParseStepError)?;
+        if label.is_empty() {
+            return Err(ParseStepError);
+        }
+        let focal_length = focal_length.parse().map_err(|_| ParseStepError)?;
+        Ok(Self::Insert(Lens {
+            label: label.to_string(),
+            focal_length,
+        }))
+    }
+}
+
+fn read_steps(input: &str) -> impl Iterator<Item = (&str, Result<Step, ParseStepError>)> {
+    input.trim().split(',').map(|step| (step, step.parse()))
+}
+
 #[derive(Debug, PartialEq)]
 struct LightBoxArray {
     boxes: Vec<Vec<Lens>>,
@@ -42,25 +77,11 @@ impl LightBoxArray {
         self.boxes[hash(label)].retain(|lens| lens.label != label);
     }
 
-    fn step(&mut self, step: &str) -> bool {
-        if let Some(label) = step.strip_suffix('-') {
-            self.remove(label);
-            return true;
+    fn step(&mut self, step: Step) {
+        match step {
+            Step::Remove(label) => self.remove(&label),
+            Step::Insert(lens) => self.insert(lens),
         }
-
-        if let Some((label, focal_length)) = step.split_once('=') {
-            if let Ok(focal_length) = focal_length.parse() {
-                let label = label.to_string();
-                let lens = Lens {
-                    label,
-                    focal_length,
-                };
-                self.insert(lens);
-                return true;
-            }
-        }
-
-        false
     }
 
     fn total_focusing_power(&self) -> usize {
@@ -77,6 +98,23 @@ impl LightBoxArray {
     }
 }
 
+/// Lists the non-empty boxes in the same format as the puzzle description.
+impl fmt::Display for LightBoxArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (box_ix, lightbox) in self.boxes.iter().enumerate() {
+            if lightbox.is_empty() {
+                continue;
+            }
+            write!(f, "Box {box_ix}:")?;
+            for lens in lightbox {
+                write!(f, " [{} {}]", lens.label, lens.focal_length)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
 #[must_use]
 pub fn part_one(input: &str) -> Option<usize> {
     Some(input.trim().split(',').map(hash).sum())
@@ -85,22 +123,52 @@ pub fn part_one(input: &str) -> Option<usize> {
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
     let mut array = LightBoxArray::new();
-    for step in input.trim().split(',') {
-        array.step(step);
+    // malformed steps are skipped, just as `--replay` skips them.
+    for (_, step) in read_steps(input) {
+        if let Ok(step) = step {
+            array.step(step);
+        }
     }
     Some(array.total_focusing_power())
 }
 
+/// With `--replay`, runs through the initialisation sequence printing the boxes after each step,
+/// and reports any malformed steps (which are skipped).
+fn replay(input: &str) {
+    if !advent_of_code::template::runner::has_flag("--replay") {
+        return;
+    }
+
+    let mut array = LightBoxArray::new();
+    for (ix, (text, step)) in read_steps(input).enumerate() {
+        match step {
+            Ok(step) => {
+                array.step(step);
+                println!("After \"{text}\":\n{array}");
+            }
+            Err(ParseStepError) => eprintln!("Step {} is malformed: \"{text}\"\n", ix + 1),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_hash() {
-        assert_eq!(hash("rn=1"), 30);
-        assert_eq!(hash("qp-"), 14);
-        assert_eq!(hash("ab=5"), 197);
-        assert_eq!(hash("ot=7"), 231);
+    fn test_parse_step() {
+        assert_eq!("cm-".parse(), Ok(Step::Remove("cm".to_string())));
+        assert_eq!(
+            "ot=9".parse(),
+            Ok(Step::Insert(Lens {
+                label: "ot".to_string(),
+                focal_length: 9
+            }))
+        );
+        assert_eq!("ot=".parse::<Step>(), Err(ParseStepError));
+        assert_eq!("ot".parse::<Step>(), Err(ParseStepError));
+        assert_eq!("-".parse::<Step>(), Err(ParseStepError));
+        assert_eq!("=3".parse::<Step>(), Err(ParseStepError));
     }
 
     #[test]
@@ -113,7 +181,7 @@ mod tests {
     fn test_lightbox_array() {
         let mut array = LightBoxArray::new();
 
-        array.step("rn=1");
+        array.step("rn=1".parse().unwrap());
         assert_eq!(
             array.boxes[0],
             vec![Lens {
@@ -122,8 +190,8 @@ mod tests {
             }],
         );
 
-        array.step("cm-");
-        array.step("qp=3");
+        array.step("cm-".parse().unwrap());
+        array.step("qp=3".parse().unwrap());
         assert_eq!(
             array.boxes[0],
             vec![Lens {
@@ -139,9 +207,9 @@ mod tests {
             }],
         );
 
-        array.step("pc=4");
-        array.step("ot=9");
-        array.step("ab=5");
+        array.step("pc=4".parse().unwrap());
+        array.step("ot=9".parse().unwrap());
+        array.step("ab=5".parse().unwrap());
         assert_eq!(
             array.boxes[3],
             vec![
@@ -159,7 +227,7 @@ mod tests {
                 },
             ],
         );
-        array.step("ot=13");
+        array.step("ot=13".parse().unwrap());
         assert_eq!(
             array.boxes[3],
             vec![
@@ -177,7 +245,7 @@ mod tests {
                 },
             ],
         );
-        array.step("pc-");
+        array.step("pc-".parse().unwrap());
         assert_eq!(
             array.boxes[3],
             vec![
@@ -193,9 +261,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_display() {
+        let mut array = LightBoxArray::new();
+        for step in ["rn=1", "cm-", "qp=3", "cm=2", "qp-", "pc=4", "ot=9"] {
+            array.step(step.parse().unwrap());
+        }
+        assert_eq!(
+            array.to_string(),
+            "Box 0: [rn 1] [cm 2]\nBox 3: [pc 4] [ot 9]\n"
+        );
+    }
+
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(145));
+        // malformed steps are skipped, leaving only "rn=1".
+        assert_eq!(part_two("rn=1,qp,cm-"), Some(1));
+        assert_eq!(part_two("rn=1,-,=3"), Some(1));
     }
 }
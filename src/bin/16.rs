@@ -1,7 +1,11 @@
-use std::collections::VecDeque;
+use advent_of_code::image;
+use advent_of_code::render::{self, Cell, Colour, Frame, Style};
+use std::panic;
 use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
-advent_of_code::solution!(16);
+advent_of_code::solution!(16, extras);
 
 const GRID_SIZE: usize = 110;
 
@@ -55,10 +59,20 @@ enum SplitterType {
 }
 
 impl SplitterType {
+    /// The directions a beam is sent in when it hits the flat side of the splitter.
+    const fn split_directions(self) -> [Direction; 2] {
+        match self {
+            Self::Vertical => [Direction::North, Direction::South],
+            Self::Horizontal => [Direction::West, Direction::East],
+        }
+    }
+
     const fn next_directions(self, current: Direction) -> (Option<Direction>, Option<Direction>) {
         match (self, current.is_vertical()) {
-            (Self::Vertical, false) => (Some(Direction::North), Some(Direction::South)),
-            (Self::Horizontal, true) => (Some(Direction::West), Some(Direction::East)),
+            (Self::Vertical, false) | (Self::Horizontal, true) => {
+                let [first, second] = self.split_directions();
+                (Some(first), Some(second))
+            }
             _ => (Some(current), None),
         }
     }
@@ -110,6 +124,39 @@ impl EnergisationTracker {
     }
 }
 
+/// A set of grid positions, stored as one bit per position.
+#[derive(Clone, Debug, PartialEq)]
+struct CellSet(Vec<u64>);
+
+impl CellSet {
+    fn new() -> Self {
+        Self(vec![0; (GRID_SIZE * GRID_SIZE).div_ceil(64)])
+    }
+
+    fn insert(&mut self, pos: usize) {
+        self.0[pos / 64] |= 1 << (pos % 64);
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        for (word, other) in self.0.iter_mut().zip(&other.0) {
+            *word |= other;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+/// The path of a beam from some position until it is split or leaves the grid.
+#[derive(Debug, PartialEq)]
+struct Segment {
+    /// Every position the beam passes through, including the splitter it ends at.
+    cells: Vec<usize>,
+    /// The position of the splitter which splits the beam, if it reaches one.
+    split_by: Option<usize>,
+}
+
 #[derive(Debug, PartialEq)]
 struct Contraption {
     grid: [Space; GRID_SIZE * GRID_SIZE],
@@ -152,50 +199,337 @@ impl Contraption {
         }
     }
 
-    fn energised_cells(&self, start_pos: usize, start_facing: Direction) -> usize {
+    /// Follows the beam from the given start, calling `on_wave` with the cells energised so far
+    /// each time every part of the beam has moved forward by one space.
+    fn energise(
+        &self,
+        start_pos: usize,
+        start_facing: Direction,
+        mut on_wave: impl FnMut(&EnergisationTracker),
+    ) -> EnergisationTracker {
         let mut visited = EnergisationTracker::new();
-        let mut queue = VecDeque::new();
+        let mut wave = vec![(start_pos, start_facing)];
+
+        while !wave.is_empty() {
+            let mut next_wave = Vec::new();
+
+            for (pos, direction) in wave {
+                if !visited.visit(pos, direction) {
+                    let (first, second) = self.grid[pos].next_directions(direction);
+
+                    if let Some(direction) = first {
+                        if let Some(next_pos) = Self::step(pos, direction) {
+                            next_wave.push((next_pos, direction));
+                        }
+                    }
+
+                    if let Some(direction) = second {
+                        if let Some(next_pos) = Self::step(pos, direction) {
+                            next_wave.push((next_pos, direction));
+                        }
+                    }
+                }
+            }
+
+            on_wave(&visited);
+            wave = next_wave;
+        }
+
+        visited
+    }
 
-        queue.push_back((start_pos, start_facing));
+    fn energised_cells(&self, start_pos: usize, start_facing: Direction) -> usize {
+        self.energise(start_pos, start_facing, |_| ())
+            .energised_cells()
+    }
 
-        while let Some((pos, direction)) = queue.pop_front() {
-            if !visited.visit(pos, direction) {
-                let (first, second) = self.grid[pos].next_directions(direction);
+    /// Every position and direction a beam can enter the contraption from the edge.
+    fn edge_entries() -> impl Iterator<Item = (usize, Direction)> {
+        (0..GRID_SIZE).flat_map(|row_or_col| {
+            let south = (GRID_SIZE * GRID_SIZE) - 1 - row_or_col;
+            let west = GRID_SIZE * row_or_col;
+            let east = west + GRID_SIZE - 1;
+            [
+                (row_or_col, Direction::South),
+                (south, Direction::North),
+                (west, Direction::East),
+                (east, Direction::West),
+            ]
+        })
+    }
+
+    /// Finds the most cells a beam from the edge can energise by following the beam from every
+    /// edge entry in turn.
+    fn most_energised_cells(&self) -> usize {
+        Self::edge_entries()
+            .map(|(pos, direction)| self.energised_cells(pos, direction))
+            .max()
+            .unwrap_or_default()
+    }
 
-                if let Some(direction) = first {
-                    if let Some(next_pos) = Self::step(pos, direction) {
-                        queue.push_back((next_pos, direction));
+    /// Follows a beam from the given start until it is split or leaves the grid.
+    fn trace(&self, start_pos: usize, start_facing: Direction) -> Segment {
+        let mut cells = Vec::new();
+        let (mut pos, mut direction) = (start_pos, start_facing);
+
+        // a beam which isn't split is back where it started by the time it has passed through
+        // every position in every direction, so stop it there.
+        for _ in 0..(4 * GRID_SIZE * GRID_SIZE) {
+            cells.push(pos);
+            match self.grid[pos].next_directions(direction) {
+                (Some(next), None) => match Self::step(pos, next) {
+                    Some(next_pos) => (pos, direction) = (next_pos, next),
+                    None => break,
+                },
+                _ => {
+                    return Segment {
+                        cells,
+                        split_by: Some(pos),
                     }
                 }
+            }
+        }
+
+        Segment {
+            cells,
+            split_by: None,
+        }
+    }
+}
+
+/// The beams leaving each splitter, worked out once so that a beam from the edge only needs to
+/// be followed as far as the first splitter which splits it.
+///
+/// Beams can split back into splitters they have already passed through, so the splitters which
+/// can reach each other are grouped together (as strongly connected components). The groups form
+/// an acyclic graph, so the cells energised from each group are its own cells plus those of the
+/// groups it reaches.
+#[derive(Debug)]
+struct BeamGraph<'a> {
+    contraption: &'a Contraption,
+    /// The index of the splitter at each position, if there is one.
+    splitters: Vec<Option<usize>>,
+    /// The group each splitter belongs to.
+    groups: Vec<usize>,
+    /// The cells energised once a beam has been split by any splitter in each group.
+    energised: Vec<CellSet>,
+}
 
-                if let Some(direction) = second {
-                    if let Some(next_pos) = Self::step(pos, direction) {
-                        queue.push_back((next_pos, direction));
+impl<'a> BeamGraph<'a> {
+    fn new(contraption: &'a Contraption) -> Self {
+        let mut splitters = vec![None; GRID_SIZE * GRID_SIZE];
+        let mut cells = Vec::new();
+        let mut successors = Vec::new();
+
+        let positions: Vec<(usize, SplitterType)> = (0..GRID_SIZE * GRID_SIZE)
+            .filter_map(|pos| match contraption.grid[pos] {
+                Space::Splitter(splitter_type) => Some((pos, splitter_type)),
+                _ => None,
+            })
+            .collect();
+        for (ix, (pos, _)) in positions.iter().enumerate() {
+            splitters[*pos] = Some(ix);
+        }
+
+        // the cells lit by the two beams leaving each splitter, and the splitters they reach
+        for (pos, splitter_type) in positions {
+            let mut lit = CellSet::new();
+            let mut next = Vec::new();
+            lit.insert(pos);
+
+            for direction in splitter_type.split_directions() {
+                let Some(start) = Contraption::step(pos, direction) else {
+                    continue;
+                };
+                let segment = contraption.trace(start, direction);
+                for cell in segment.cells {
+                    lit.insert(cell);
+                }
+                next.extend(segment.split_by.and_then(|end| splitters[end]));
+            }
+
+            cells.push(lit);
+            successors.push(next);
+        }
+
+        // components come out with every component they reach ahead of them, so those are
+        // always complete by the time they are needed.
+        let mut groups = vec![0; successors.len()];
+        let mut energised: Vec<CellSet> = Vec::new();
+        for (group, members) in strongly_connected(&successors).into_iter().enumerate() {
+            let mut lit = CellSet::new();
+            for member in &members {
+                groups[*member] = group;
+            }
+            for member in members {
+                lit.union_with(&cells[member]);
+                for next in &successors[member] {
+                    if groups[*next] != group {
+                        lit.union_with(&energised[groups[*next]]);
                     }
                 }
             }
+            energised.push(lit);
         }
 
-        visited.energised_cells()
+        Self {
+            contraption,
+            splitters,
+            groups,
+            energised,
+        }
+    }
+
+    fn energised_cells(&self, start_pos: usize, start_facing: Direction) -> usize {
+        let segment = self.contraption.trace(start_pos, start_facing);
+        let mut lit = segment
+            .split_by
+            .and_then(|end| self.splitters[end])
+            .map_or_else(CellSet::new, |splitter| {
+                self.energised[self.groups[splitter]].clone()
+            });
+        for cell in segment.cells {
+            lit.insert(cell);
+        }
+        lit.len()
     }
 
     fn most_energised_cells(&self) -> usize {
-        let mut most = 0;
+        Contraption::edge_entries()
+            .map(|(pos, direction)| self.energised_cells(pos, direction))
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// As [`Self::most_energised_cells`], sharing the edge entries out between `threads` threads.
+    fn most_energised_cells_parallel(&self, threads: usize) -> usize {
+        let entries: Vec<(usize, Direction)> = Contraption::edge_entries().collect();
+        let chunk_size = entries.len().div_ceil(threads.max(1)).max(1);
+
+        thread::scope(|scope| {
+            let mut workers = Vec::new();
+            for chunk in entries.chunks(chunk_size) {
+                workers.push(scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(pos, direction)| self.energised_cells(*pos, *direction))
+                        .max()
+                }));
+            }
+            workers
+                .into_iter()
+                // a worker which panicked hasn't checked its entries, so pass the panic on.
+                .filter_map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|err| panic::resume_unwind(err))
+                })
+                .max()
+                .unwrap_or_default()
+        })
+    }
+}
 
-        for row_or_col in 0..GRID_SIZE {
-            most = most.max(self.energised_cells(row_or_col, Direction::South));
+/// Tarjan's algorithm, finding the strongly connected components of a graph given as the
+/// successors of each node.
+struct Tarjan<'a> {
+    successors: &'a [Vec<usize>],
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    components: Vec<Vec<usize>>,
+}
 
-            let south = (GRID_SIZE * GRID_SIZE) - 1 - row_or_col;
-            most = most.max(self.energised_cells(south, Direction::North));
+impl Tarjan<'_> {
+    fn visit(&mut self, node: usize) {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.indices[node] = Some(index);
+        self.low_links[node] = index;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &next in &self.successors[node] {
+            match self.indices[next] {
+                None => {
+                    self.visit(next);
+                    self.low_links[node] = self.low_links[node].min(self.low_links[next]);
+                }
+                Some(next_index) if self.on_stack[next] => {
+                    self.low_links[node] = self.low_links[node].min(next_index);
+                }
+                Some(_) => (),
+            }
+        }
 
-            let west = GRID_SIZE * row_or_col;
-            most = most.max(self.energised_cells(west, Direction::East));
+        if self.low_links[node] == index {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
 
-            let east = west + GRID_SIZE - 1;
-            most = most.max(self.energised_cells(east, Direction::West));
+/// The strongly connected components of the graph, each listed after every component which can
+/// be reached from it.
+fn strongly_connected(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut tarjan = Tarjan {
+        successors,
+        indices: vec![None; successors.len()],
+        low_links: vec![0; successors.len()],
+        on_stack: vec![false; successors.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..successors.len() {
+        if tarjan.indices[node].is_none() {
+            tarjan.visit(node);
         }
+    }
+    tarjan.components
+}
 
-        most
+impl Contraption {
+    /// Draws the top-left `rows` by `cols` of the contraption in the same way as the puzzle
+    /// description: empty energised tiles show the direction of the beam through them, or the
+    /// number of beams if there is more than one.
+    fn frame(&self, energised: &EnergisationTracker, rows: usize, cols: usize) -> Frame {
+        let lit = Colour::ORANGE.blend(Colour::BLACK, 2, 3);
+
+        Frame::from_fn(cols, rows, |row, col| {
+            let pos = (row * GRID_SIZE) + col;
+            let beams = energised.grid[pos];
+            let symbol = match self.grid[pos] {
+                Space::Mirror(MirrorType::Forwardslash) => '/',
+                Space::Mirror(MirrorType::Backslash) => '\\',
+                Space::Splitter(SplitterType::Vertical) => '|',
+                Space::Splitter(SplitterType::Horizontal) => '-',
+                Space::Empty => match beams {
+                    0 => '.',
+                    1 => '^',
+                    2 => '>',
+                    4 => 'v',
+                    8 => '<',
+                    _ => char::from_digit(beams.count_ones(), 10).unwrap_or('#'),
+                },
+            };
+
+            let style = match self.grid[pos] {
+                Space::Empty if beams == 0 => Style::fg(Colour::GREY),
+                Space::Empty => Style::fg(Colour::YELLOW),
+                _ => Style::fg(Colour::WHITE).bold(),
+            };
+            Cell::new(symbol, if beams == 0 { style } else { style.on(lit) })
+        })
     }
 }
 
@@ -243,12 +577,73 @@ pub fn part_one(input: &str) -> Option<usize> {
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<usize> {
-    Contraption::from_str(input).map_or(None, |contrap| Some(contrap.most_energised_cells()))
+    Contraption::from_str(input).map_or(None, |contrap| {
+        Some(BeamGraph::new(&contrap).most_energised_cells())
+    })
+}
+
+/// With `--visualise`, animates the beam spreading through the contraption for part one. With
+/// `--export <file>`, saves the fully energised contraption as an image. With `--threads <n>`,
+/// also solves part two using `n` threads, and with `--bench`, compares following the beam from
+/// every edge entry against working from the beams between splitters, with one thread and with
+/// every available thread.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{benchmark, flag_value, has_flag};
+
+    let export = flag_value("--export");
+    let threads = flag_value("--threads").and_then(|threads| threads.parse::<usize>().ok());
+    if !has_flag("--visualise") && !has_flag("--bench") && export.is_none() && threads.is_none() {
+        return;
+    }
+    let Ok(contraption) = Contraption::from_str(input) else {
+        eprintln!("Failed to read contraption");
+        return;
+    };
+
+    if let Some(threads) = threads {
+        let most = BeamGraph::new(&contraption).most_energised_cells_parallel(threads);
+        println!("Part 2 with {threads} threads: {most}");
+    }
+    if has_flag("--bench") {
+        let available = thread::available_parallelism().map_or(1, usize::from);
+        benchmark(
+            "Following every beam",
+            Contraption::most_energised_cells,
+            &contraption,
+        );
+        benchmark(
+            "Beams between splitters",
+            |contraption: &Contraption| BeamGraph::new(contraption).most_energised_cells(),
+            &contraption,
+        );
+        benchmark(
+            &format!("Beams between splitters with {available} threads"),
+            |contraption: &Contraption| {
+                BeamGraph::new(contraption).most_energised_cells_parallel(available)
+            },
+            &contraption,
+        );
+    }
+
+    if has_flag("--visualise") || export.is_some() {
+        let (rows, cols) = render::dimensions(input);
+        let mut frames = Vec::new();
+        contraption.energise(0, Direction::East, |energised| {
+            frames.push(contraption.frame(energised, rows, cols));
+        });
+        if let (Some(path), Some(last)) = (export, frames.last()) {
+            image::export(last, &path);
+        }
+        if has_flag("--visualise") {
+            render::animate(frames, Duration::from_millis(30));
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::random::Lcg;
 
     fn position(row: usize, col: usize) -> usize {
         (row * GRID_SIZE) + col
@@ -323,6 +718,111 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_frame() {
+        let contraption = example_contraption();
+        let energised = contraption.energise(0, Direction::East, |_| ());
+        assert_eq!(
+            contraption.frame(&energised, 10, 10).plain(),
+            [
+                ">|<<<\\....\n",
+                "|v-.\\^....\n",
+                ".v...|->>>\n",
+                ".v...v^.|.\n",
+                ".v...v^...\n",
+                ".v...v^..\\\n",
+                ".v../2\\\\..\n",
+                "<->-/vv|..\n",
+                ".|<<<2-|.\\\n",
+                ".v//.|.v..\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let contraption = example_contraption();
+        assert_eq!(
+            contraption.trace(0, Direction::East),
+            Segment {
+                cells: vec![0, 1],
+                split_by: Some(1)
+            }
+        );
+        // reflected south, the beam passes straight through both vertical splitters.
+        let segment = contraption.trace(position(0, 2), Direction::East);
+        assert_eq!(
+            segment.cells[..6],
+            [2, 3, 4, 5, position(1, 5), position(2, 5)]
+        );
+        assert_eq!(segment.cells.len(), 4 + GRID_SIZE - 1);
+        assert_eq!(segment.split_by, None);
+        assert_eq!(
+            contraption.trace(position(3, 0), Direction::East),
+            Segment {
+                cells: (position(3, 0)..=position(3, 8)).collect(),
+                split_by: Some(position(3, 8))
+            }
+        );
+        let segment = contraption.trace(position(4, 0), Direction::East);
+        assert_eq!(segment.cells.len(), GRID_SIZE);
+        assert_eq!(segment.split_by, None);
+    }
+
+    #[test]
+    fn test_strongly_connected() {
+        let successors = vec![vec![1], vec![2, 3], vec![0], vec![4], vec![3], vec![]];
+        let mut components = strongly_connected(&successors);
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        assert_eq!(components, vec![vec![3, 4], vec![0, 1, 2], vec![5]]);
+    }
+
+    fn random_contraption(random: &mut Lcg) -> Contraption {
+        let mut input = String::new();
+        for _ in 0..GRID_SIZE {
+            for _ in 0..GRID_SIZE {
+                input.push(match random.below(40) {
+                    0 => '/',
+                    1 => '\\',
+                    2 => '-',
+                    3 => '|',
+                    _ => '.',
+                });
+            }
+            input.push('\n');
+        }
+        input.parse().unwrap()
+    }
+
+    fn check_beam_graph(contraption: &Contraption) {
+        let beams = BeamGraph::new(contraption);
+        for (pos, direction) in Contraption::edge_entries().step_by(5) {
+            assert_eq!(
+                beams.energised_cells(pos, direction),
+                contraption.energised_cells(pos, direction)
+            );
+        }
+        assert_eq!(
+            beams.most_energised_cells(),
+            contraption.most_energised_cells()
+        );
+        for threads in 1..=3 {
+            assert_eq!(
+                beams.most_energised_cells_parallel(threads),
+                beams.most_energised_cells()
+            );
+        }
+    }
+
+    #[test]
+    fn test_beam_graph() {
+        check_beam_graph(&example_contraption());
+        check_beam_graph(&random_contraption(&mut Lcg::new(16)));
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -1,10 +1,10 @@
-use std::cmp::Ordering;
+use advent_of_code::image;
+use advent_of_code::render::{Cell, Colour, Frame, Style};
+use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
 use std::str::FromStr;
 
-advent_of_code::solution!(17);
-
-const GRID_SIZE: usize = 141;
+advent_of_code::solution!(17, extras);
 
 #[derive(Copy, Clone, Eq, Debug, Ord, PartialEq, PartialOrd)]
 enum Direction {
@@ -32,6 +32,57 @@ impl Direction {
             Self::West => Self::North,
         }
     }
+
+    const fn arrow(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::East => '>',
+            Self::South => 'v',
+            Self::West => '<',
+        }
+    }
+}
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
+/// How a crucible is allowed to move: after setting off in a direction it must move at least
+/// `min_run` and at most `max_run` blocks in a straight line before turning or stopping, and it
+/// can only turn around to go back the way it came if `reverse` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Crucible {
+    min_run: usize,
+    max_run: usize,
+    reverse: bool,
+}
+
+const CRUCIBLE: Crucible = Crucible {
+    min_run: 1,
+    max_run: 3,
+    reverse: false,
+};
+
+const ULTRA_CRUCIBLE: Crucible = Crucible {
+    min_run: 4,
+    max_run: 10,
+    reverse: false,
+};
+
+impl Crucible {
+    /// The directions the crucible can set off in at the end of a run in direction `facing`.
+    fn turns(self, facing: Direction) -> impl Iterator<Item = Direction> {
+        [
+            Some(facing.turn_left()),
+            Some(facing.turn_right()),
+            self.reverse.then(|| facing.turn_left().turn_left()),
+        ]
+        .into_iter()
+        .flatten()
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -52,6 +103,19 @@ impl Ord for JourneyState {
     }
 }
 
+impl JourneyState {
+    /// The index of this state's position and direction in a [`JourneyVisitTracker`].
+    const fn key(&self) -> usize {
+        let dir = match self.facing {
+            Direction::North => 0,
+            Direction::East => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+        };
+        (4 * self.position) + dir
+    }
+}
+
 impl PartialOrd for JourneyState {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
@@ -60,14 +124,16 @@ impl PartialOrd for JourneyState {
 
 #[derive(Debug)]
 struct JourneyVisitTracker {
-    visited: [u32; GRID_SIZE * GRID_SIZE * 4],
+    visited: Vec<u32>,
+    /// The key of the state each state was best reached from, or `None` for initial states.
+    parents: Vec<Option<usize>>,
 }
 
 impl JourneyVisitTracker {
-    #[allow(clippy::large_stack_frames)]
-    const fn new() -> Self {
+    fn new(blocks: usize) -> Self {
         Self {
-            visited: [u32::MAX; GRID_SIZE * GRID_SIZE * 4],
+            visited: vec![u32::MAX; blocks * 4],
+            parents: vec![None; blocks * 4],
         }
     }
 
@@ -77,47 +143,156 @@ impl JourneyVisitTracker {
     }
 
     fn visit(&mut self, state: &JourneyState) -> bool {
-        let dir = match state.facing {
-            Direction::North => 0,
-            Direction::East => 1,
-            Direction::South => 2,
-            Direction::West => 3,
-        };
-        let key = (4 * state.position) + dir;
+        self.visit_from(state, None)
+    }
+
+    /// Records `state` (reached from `parent`) unless it has already been reached with the same
+    /// or less heat loss, returning whether it had.
+    fn visit_from(&mut self, state: &JourneyState, parent: Option<&JourneyState>) -> bool {
+        let key = state.key();
 
         if self.visited[key] <= state.heat_loss {
             true
         } else {
             self.visited[key] = state.heat_loss;
+            self.parents[key] = parent.map(JourneyState::key);
             false
         }
     }
+
+    /// Whether a better way to reach `state` has been found since it was queued.
+    fn superseded(&self, state: &JourneyState) -> bool {
+        self.visited[state.key()] < state.heat_loss
+    }
+
+    /// The positions where the crucible turns on its best journey to the state with `key`, along
+    /// with the direction it moved to get to each one.
+    fn journey(&self, key: usize) -> Vec<(usize, Direction)> {
+        let mut stops = Vec::new();
+        let mut current = Some(key);
+        while let Some(key) = current {
+            stops.push((key / 4, DIRECTIONS[key % 4]));
+            current = self.parents[key];
+        }
+        stops.reverse();
+        stops
+    }
 }
 
 #[derive(Debug, PartialEq)]
 struct City {
-    grid: [u32; GRID_SIZE * GRID_SIZE],
+    rows: usize,
+    cols: usize,
+    grid: Vec<u32>,
 }
 
 impl City {
-    fn minimal_heat_loss(&self, min_dist: usize, max_dist: usize) -> Option<u32> {
-        let mut visited = JourneyVisitTracker::new();
+    /// The position of the factory, in the bottom-right corner.
+    const fn target(&self) -> usize {
+        self.grid.len() - 1
+    }
+
+    fn minimal_heat_loss(&self, crucible: Crucible) -> Option<u32> {
+        self.search(crucible)
+            .map(|(visited, arrival)| visited.visited[arrival])
+    }
+
+    /// Every block the crucible enters on a journey with the least heat loss, with the direction
+    /// it is moving as it enters it.
+    fn route(&self, crucible: Crucible) -> Option<Vec<(usize, Direction)>> {
+        let (visited, arrival) = self.search(crucible)?;
+
+        let mut route = Vec::new();
+        let mut position = 0;
+        for (stop, facing) in visited.journey(arrival) {
+            while position != stop {
+                position = self.step(position, facing, 1)?;
+                route.push((position, facing));
+            }
+        }
+        Some(route)
+    }
+
+    /// Finds the least heat loss with which the crucible can arrive at each block, from each
+    /// direction.
+    fn explore(&self, crucible: Crucible) -> JourneyVisitTracker {
+        let mut visited = JourneyVisitTracker::new(self.grid.len());
         let mut queue = BinaryHeap::new();
-        for state in self.initial_states(min_dist, max_dist) {
+        for state in self.initial_states(crucible) {
             if !visited.visit(&state) {
                 queue.push(state);
             }
         }
 
         while let Some(state) = queue.pop() {
-            for reachable in self.reachable_states(&state, min_dist, max_dist) {
-                if !visited.visit(&reachable) {
+            for reachable in self.reachable_states(&state, crucible) {
+                if !visited.visit_from(&reachable, Some(&state)) {
                     queue.push(reachable);
                 }
             }
         }
 
-        visited.minimum((GRID_SIZE * GRID_SIZE) - 1)
+        visited
+    }
+
+    /// The least heat loss on the way to the factory from each block, if the crucible could move
+    /// however it liked. This never overestimates, so it can guide an A* search.
+    fn heuristic(&self) -> Vec<u32> {
+        let mut remaining = vec![u32::MAX; self.grid.len()];
+        let mut queue = BinaryHeap::new();
+        remaining[self.target()] = 0;
+        queue.push(Reverse((0, self.target())));
+
+        while let Some(Reverse((loss, position))) = queue.pop() {
+            if loss > remaining[position] {
+                continue;
+            }
+            // moving from a neighbour onto this block loses this block's heat.
+            let loss = loss + self.grid[position];
+            for neighbour in DIRECTIONS
+                .iter()
+                .filter_map(|dir| self.step(position, *dir, 1))
+            {
+                if loss < remaining[neighbour] {
+                    remaining[neighbour] = loss;
+                    queue.push(Reverse((loss, neighbour)));
+                }
+            }
+        }
+
+        remaining
+    }
+
+    /// Searches for the least heat loss with which the crucible can reach the factory, using A*
+    /// guided by the [`City::heuristic`]. Returns the tracker of states visited along with the
+    /// key of the state in which the crucible arrives.
+    fn search(&self, crucible: Crucible) -> Option<(JourneyVisitTracker, usize)> {
+        let remaining = self.heuristic();
+        let estimate = |state: &JourneyState| Reverse(state.heat_loss + remaining[state.position]);
+
+        let mut visited = JourneyVisitTracker::new(self.grid.len());
+        let mut queue = BinaryHeap::new();
+        for state in self.initial_states(crucible) {
+            if !visited.visit(&state) {
+                queue.push((estimate(&state), state));
+            }
+        }
+
+        while let Some((_, state)) = queue.pop() {
+            if visited.superseded(&state) {
+                continue;
+            }
+            if state.position == self.target() {
+                return Some((visited, state.key()));
+            }
+            for reachable in self.reachable_states(&state, crucible) {
+                if !visited.visit_from(&reachable, Some(&state)) {
+                    queue.push((estimate(&reachable), reachable));
+                }
+            }
+        }
+
+        None
     }
 
     fn states_in_directions<'a>(
@@ -125,72 +300,60 @@ impl City {
         position: usize,
         heat_loss: u32,
         directions: impl Iterator<Item = Direction> + 'a,
-        min_dist: usize,
-        max_dist: usize,
+        crucible: Crucible,
     ) -> impl Iterator<Item = JourneyState> + 'a {
         directions.flat_map(move |facing| {
             let mut states = Vec::new();
             let mut extra_loss = 0;
-            for dist in 1..=max_dist {
-                if let Some(position) = Self::step(position, facing, dist) {
-                    extra_loss += self.grid[position];
-                    if dist >= min_dist {
-                        states.push(JourneyState {
-                            position,
-                            facing,
-                            heat_loss: heat_loss + extra_loss,
-                        });
-                    }
+            for dist in 1..=crucible.max_run {
+                let Some(position) = self.step(position, facing, dist) else {
+                    break;
+                };
+                extra_loss += self.grid[position];
+                if dist >= crucible.min_run {
+                    states.push(JourneyState {
+                        position,
+                        facing,
+                        heat_loss: heat_loss + extra_loss,
+                    });
                 }
             }
             states
         })
     }
 
-    fn initial_states(
-        &self,
-        min_dist: usize,
-        max_dist: usize,
-    ) -> impl Iterator<Item = JourneyState> + '_ {
-        self.states_in_directions(
-            0,
-            0,
-            [Direction::East, Direction::South].into_iter(),
-            min_dist,
-            max_dist,
-        )
+    fn initial_states(&self, crucible: Crucible) -> impl Iterator<Item = JourneyState> + '_ {
+        self.states_in_directions(0, 0, DIRECTIONS.into_iter(), crucible)
     }
 
     fn reachable_states<'a>(
         &'a self,
         state: &'a JourneyState,
-        min_dist: usize,
-        max_dist: usize,
+        crucible: Crucible,
     ) -> impl Iterator<Item = JourneyState> + 'a {
         self.states_in_directions(
             state.position,
             state.heat_loss,
-            [state.facing.turn_left(), state.facing.turn_right()].into_iter(),
-            min_dist,
-            max_dist,
+            crucible.turns(state.facing),
+            crucible,
         )
     }
 
-    const fn step(pos: usize, dir: Direction, dist: usize) -> Option<usize> {
-        let row = pos / GRID_SIZE;
-        let col = pos % GRID_SIZE;
+    const fn step(&self, pos: usize, dir: Direction, dist: usize) -> Option<usize> {
+        let row = pos / self.cols;
+        let col = pos % self.cols;
         match dir {
-            Direction::North => pos.checked_sub(GRID_SIZE * dist),
+            Direction::North => pos.checked_sub(self.cols * dist),
             Direction::East => {
-                if (col + dist) < GRID_SIZE {
+                if (col + dist) < self.cols {
                     Some(pos + dist)
                 } else {
                     None
                 }
             }
             Direction::South => {
-                if (row + dist) < GRID_SIZE {
-                    Some(pos + (GRID_SIZE * dist))
+                if (row + dist) < self.rows {
+                    Some(pos + (self.cols * dist))
                 } else {
                     None
                 }
@@ -206,6 +369,49 @@ impl City {
     }
 }
 
+impl City {
+    /// Draws the city, shaded by the least heat loss with which each block can be reached.
+    fn frame(&self, visited: &JourneyVisitTracker) -> Frame {
+        let shades = [Colour::GREEN, Colour::YELLOW, Colour::RED];
+        let least = |pos: usize| visited.minimum(pos).filter(|loss| *loss != u32::MAX);
+        let most = (0..self.grid.len())
+            .filter_map(least)
+            .max()
+            .unwrap_or_default();
+
+        Frame::from_fn(self.cols, self.rows, |row, col| {
+            let pos = (row * self.cols) + col;
+            let symbol = char::from_digit(self.grid[pos], 10).unwrap_or('?');
+            let style = least(pos).map_or(Style::fg(Colour::GREY), |loss| {
+                let shade = Colour::gradient(&shades, loss as usize, most as usize);
+                Style::fg(Colour::BLACK).on(shade)
+            });
+            Cell::new(symbol, style)
+        })
+    }
+}
+
+impl City {
+    /// Draws the city with the route marked out in arrows, as in the puzzle description.
+    fn route_frame(&self, route: &[(usize, Direction)]) -> Frame {
+        let mut frame = Frame::from_fn(self.cols, self.rows, |row, col| {
+            let value = self.grid[(row * self.cols) + col];
+            Cell::new(
+                char::from_digit(value, 10).unwrap_or('?'),
+                Style::fg(Colour::GREY),
+            )
+        });
+        for (position, facing) in route {
+            frame.set(
+                position / self.cols,
+                position % self.cols,
+                Cell::new(facing.arrow(), Style::fg(Colour::RED).bold()),
+            );
+        }
+        frame
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct ParseCityError;
 
@@ -213,42 +419,107 @@ impl FromStr for City {
     type Err = ParseCityError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut grid = [0; GRID_SIZE * GRID_SIZE];
-
-        for (row, line) in input.lines().enumerate() {
-            for (col, ch) in line.chars().enumerate() {
-                if let Some(value) = ch.to_digit(10) {
-                    grid[(row * GRID_SIZE) + col] = value;
-                } else {
-                    return Err(ParseCityError);
-                }
+        let mut grid = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+
+        for line in input.lines() {
+            if rows == 0 {
+                cols = line.len();
+            } else if line.len() != cols {
+                return Err(ParseCityError);
+            }
+            for ch in line.chars() {
+                grid.push(ch.to_digit(10).ok_or(ParseCityError)?);
             }
+            rows += 1;
         }
 
-        Ok(Self { grid })
+        if grid.is_empty() {
+            return Err(ParseCityError);
+        }
+        Ok(Self { rows, cols, grid })
     }
 }
 
 #[must_use]
 pub fn part_one(input: &str) -> Option<u32> {
-    City::from_str(input).map_or(None, |city| city.minimal_heat_loss(1, 3))
+    City::from_str(input).map_or(None, |city| city.minimal_heat_loss(CRUCIBLE))
 }
 
 #[must_use]
 pub fn part_two(input: &str) -> Option<u32> {
-    City::from_str(input).map_or(None, |city| city.minimal_heat_loss(4, 10))
+    City::from_str(input).map_or(None, |city| city.minimal_heat_loss(ULTRA_CRUCIBLE))
+}
+
+/// With `--visualise`, shows the city shaded by how much heat is lost getting to each block
+/// under the rules for part one. With `--export <file>`, saves the same picture as an image.
+/// With `--route`, draws the best route for each part over the city. With `--bench`, compares
+/// the A* search against exploring the whole city with Dijkstra's algorithm.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{benchmark, flag_value, has_flag};
+
+    let export = flag_value("--export");
+    if !has_flag("--visualise") && !has_flag("--route") && !has_flag("--bench") && export.is_none()
+    {
+        return;
+    }
+    let Ok(city) = City::from_str(input) else {
+        eprintln!("Failed to read city");
+        return;
+    };
+
+    if has_flag("--visualise") || export.is_some() {
+        let frame = city.frame(&city.explore(CRUCIBLE));
+        if has_flag("--visualise") {
+            print!("{frame}");
+        }
+        if let Some(path) = export {
+            image::export(&frame, &path);
+        }
+    }
+
+    if has_flag("--route") {
+        for (part, crucible) in [(1, CRUCIBLE), (2, ULTRA_CRUCIBLE)] {
+            match city.route(crucible) {
+                Some(route) => {
+                    let heat_loss: u32 = route.iter().map(|(pos, _)| city.grid[*pos]).sum();
+                    println!("Part {part} route (heat loss {heat_loss}):");
+                    print!("{}", city.route_frame(&route));
+                }
+                None => println!("Part {part}: no route"),
+            }
+        }
+    }
+
+    if has_flag("--bench") {
+        for (part, crucible) in [(1, CRUCIBLE), (2, ULTRA_CRUCIBLE)] {
+            benchmark(
+                &format!("Part {part} Dijkstra"),
+                |city: &City| city.explore(crucible).minimum(city.target()),
+                &city,
+            );
+            benchmark(
+                &format!("Part {part} A*"),
+                |city: &City| city.minimal_heat_loss(crucible),
+                &city,
+            );
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EXAMPLE_SIZE: usize = 13;
+
     fn position(row: usize, col: usize) -> usize {
-        (row * GRID_SIZE) + col
+        (row * EXAMPLE_SIZE) + col
     }
 
     fn example_city() -> City {
-        let mut grid = [0; GRID_SIZE * GRID_SIZE];
+        let mut grid = vec![0; EXAMPLE_SIZE * EXAMPLE_SIZE];
 
         for (row, values) in [
             [2, 4, 1, 3, 4, 3, 2, 3, 1, 1, 3, 2, 3],
@@ -269,11 +540,15 @@ mod tests {
         .enumerate()
         {
             for (col, value) in values.into_iter().enumerate() {
-                grid[(row * GRID_SIZE) + col] = value;
+                grid[position(row, col)] = value;
             }
         }
 
-        City { grid }
+        City {
+            rows: EXAMPLE_SIZE,
+            cols: EXAMPLE_SIZE,
+            grid,
+        }
     }
 
     #[test]
@@ -294,36 +569,38 @@ mod tests {
 
     #[test]
     fn test_city_step() {
+        let city = example_city();
         assert_eq!(
-            City::step(position(2, 5), Direction::North, 1),
+            city.step(position(2, 5), Direction::North, 1),
             Some(position(1, 5))
         );
         assert_eq!(
-            City::step(position(3, 7), Direction::East, 1),
+            city.step(position(3, 7), Direction::East, 1),
             Some(position(3, 8))
         );
         assert_eq!(
-            City::step(position(1, 13), Direction::South, 1),
-            Some(position(2, 13))
+            city.step(position(1, 12), Direction::South, 1),
+            Some(position(2, 12))
         );
         assert_eq!(
-            City::step(position(0, 9), Direction::West, 1),
+            city.step(position(0, 9), Direction::West, 1),
             Some(position(0, 8))
         );
     }
 
     #[test]
     fn test_city_step_oob() {
-        assert_eq!(City::step(position(0, 4), Direction::North, 1), None);
+        let city = example_city();
+        assert_eq!(city.step(position(0, 4), Direction::North, 1), None);
         assert_eq!(
-            City::step(position(4, GRID_SIZE - 1), Direction::East, 1),
+            city.step(position(4, EXAMPLE_SIZE - 1), Direction::East, 1),
             None
         );
         assert_eq!(
-            City::step(position(GRID_SIZE - 1, 12), Direction::South, 1),
+            city.step(position(EXAMPLE_SIZE - 1, 12), Direction::South, 1),
             None
         );
-        assert_eq!(City::step(position(1, 0), Direction::West, 1), None);
+        assert_eq!(city.step(position(1, 0), Direction::West, 1), None);
     }
 
     #[test]
@@ -371,7 +648,7 @@ mod tests {
             facing: Direction::East,
             heat_loss: 15,
         };
-        let reachable: Vec<JourneyState> = city.reachable_states(&state, 1, 3).collect();
+        let reachable: Vec<JourneyState> = city.reachable_states(&state, CRUCIBLE).collect();
         assert_eq!(
             reachable,
             vec![
@@ -401,7 +678,7 @@ mod tests {
 
     #[test]
     fn test_journey_visit_tracker() {
-        let mut jvt = JourneyVisitTracker::new();
+        let mut jvt = JourneyVisitTracker::new(EXAMPLE_SIZE * EXAMPLE_SIZE);
 
         let state = JourneyState {
             position: position(2, 3),
@@ -444,13 +721,13 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(45));
+        assert_eq!(result, Some(102));
     }
 
     #[test]
     fn test_initial_states_mega_crucible() {
         let city = example_city();
-        let initial: Vec<JourneyState> = city.initial_states(4, 10).collect();
+        let initial: Vec<JourneyState> = city.initial_states(ULTRA_CRUCIBLE).collect();
         assert_eq!(
             initial,
             vec![
@@ -531,6 +808,145 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(51));
+        assert_eq!(result, Some(94));
+    }
+
+    fn assert_valid_route(city: &City, route: &[(usize, Direction)], crucible: Crucible) {
+        let mut position = 0;
+        let mut run = 0;
+        for (ix, (next, facing)) in route.iter().enumerate() {
+            assert_eq!(city.step(position, *facing, 1), Some(*next));
+            run = match ix.checked_sub(1).map(|prev| route[prev].1) {
+                Some(prev) if prev == *facing => run + 1,
+                Some(prev) => {
+                    assert!(
+                        run >= crucible.min_run,
+                        "run of {run} before turning at {ix}"
+                    );
+                    assert!(
+                        crucible.reverse || prev != facing.turn_left().turn_left(),
+                        "reversed at {ix}"
+                    );
+                    1
+                }
+                None => 1,
+            };
+            assert!(run <= crucible.max_run, "run of {run} at {ix}");
+            position = *next;
+        }
+        assert!(run >= crucible.min_run);
+        assert_eq!(position, city.target());
+    }
+
+    #[test]
+    fn test_route() {
+        let city = example_city();
+        for (crucible, expected) in [(CRUCIBLE, 102), (ULTRA_CRUCIBLE, 94)] {
+            let route = city.route(crucible).expect("a route");
+            assert_valid_route(&city, &route, crucible);
+            let heat_loss: u32 = route.iter().map(|(pos, _)| city.grid[*pos]).sum();
+            assert_eq!(heat_loss, expected);
+        }
+    }
+
+    #[test]
+    fn test_route_frame() {
+        let city = example_city();
+        let route = [
+            (position(0, 1), Direction::East),
+            (position(1, 1), Direction::South),
+            (position(1, 0), Direction::West),
+            (position(0, 0), Direction::North),
+        ];
+        let frame = city.route_frame(&route);
+        assert_eq!(&frame.plain()[..28], "^>13432311323\n<v15453535623\n");
+        assert_eq!(frame.get(0, 2).unwrap().style, Style::fg(Colour::GREY));
+        assert_eq!(
+            frame.get(1, 1).unwrap().style,
+            Style::fg(Colour::RED).bold()
+        );
+    }
+
+    #[test]
+    fn test_heuristic() {
+        let city: City = "123\n456\n789".parse().unwrap();
+        assert_eq!(
+            city.heuristic(),
+            vec![
+                6 + 9 + 3 + 2,
+                6 + 9 + 3,
+                9 + 6,
+                5 + 6 + 9,
+                9 + 6,
+                9,
+                9 + 8,
+                9,
+                0
+            ]
+        );
+
+        // never more than the real heat loss, whichever way the crucible moves.
+        let city = example_city();
+        let remaining = city.heuristic();
+        let visited = city.explore(Crucible {
+            min_run: 1,
+            max_run: usize::MAX,
+            reverse: true,
+        });
+        assert_eq!(visited.minimum(city.target()), Some(remaining[0]));
+    }
+
+    #[test]
+    fn test_search_matches_explore() {
+        let city = example_city();
+        for min_run in 1..=4 {
+            for max_run in min_run..=10 {
+                for reverse in [false, true] {
+                    let crucible = Crucible {
+                        min_run,
+                        max_run,
+                        reverse,
+                    };
+                    let expected = city
+                        .explore(crucible)
+                        .minimum(city.target())
+                        .filter(|loss| *loss != u32::MAX);
+                    assert_eq!(city.minimal_heat_loss(crucible), expected, "{crucible:?}");
+                    if let Some(route) = city.route(crucible) {
+                        assert_valid_route(&city, &route, crucible);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reversing_crucible() {
+        let city: City = "11111".parse().unwrap();
+        assert_eq!(city.minimal_heat_loss(CRUCIBLE), None);
+
+        let reversing = Crucible {
+            reverse: true,
+            ..CRUCIBLE
+        };
+        assert_eq!(city.minimal_heat_loss(reversing), Some(6));
+        let route = city.route(reversing).unwrap();
+        assert_valid_route(&city, &route, reversing);
+        assert_eq!(route.len(), 6);
+    }
+
+    #[test]
+    fn test_unbalanced_crucible() {
+        let city: City = "111111111111\n999999999991\n999999999991\n999999999991\n999999999991"
+            .parse()
+            .unwrap();
+        assert_eq!(city.minimal_heat_loss(ULTRA_CRUCIBLE), Some(71));
+    }
+
+    #[test]
+    fn test_parse_city_errors() {
+        assert_eq!("".parse::<City>(), Err(ParseCityError));
+        assert_eq!("123\n45".parse::<City>(), Err(ParseCityError));
+        assert_eq!("12x".parse::<City>(), Err(ParseCityError));
     }
 }
@@ -1,7 +1,11 @@
+use advent_of_code::geometry::polygon::{Containment, Point, Polygon};
+use advent_of_code::image;
+use advent_of_code::render::{Cell, Colour, Frame, Style};
+use std::fmt;
 use std::ops::Add;
 use std::str::FromStr;
 
-advent_of_code::solution!(18);
+advent_of_code::solution!(18, extras);
 
 #[derive(Debug, PartialEq)]
 enum Direction {
@@ -17,6 +21,30 @@ struct Instruction {
     distance: i64,
 }
 
+impl Direction {
+    const fn is_opposite(&self, other: &Self) -> bool {
+        matches!(
+            (self, other),
+            (Self::Up, Self::Down)
+                | (Self::Down, Self::Up)
+                | (Self::Left, Self::Right)
+                | (Self::Right, Self::Left)
+        )
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.direction {
+            Direction::Up => 'U',
+            Direction::Right => 'R',
+            Direction::Down => 'D',
+            Direction::Left => 'L',
+        };
+        write!(f, "{direction} {}", self.distance)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct ParseInstructionError;
 
@@ -84,78 +112,196 @@ impl Instruction {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct Point(i64, i64);
-
-impl Point {
-    const fn distance(self, other: Self) -> u64 {
-        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
-    }
-}
-
 impl Add<&Instruction> for Point {
     type Output = Self;
 
     fn add(self, rhs: &Instruction) -> Self::Output {
         match rhs.direction {
-            Direction::Up => Self(self.0, self.1 - rhs.distance),
-            Direction::Right => Self(self.0 + rhs.distance, self.1),
-            Direction::Down => Self(self.0, self.1 + rhs.distance),
-            Direction::Left => Self(self.0 - rhs.distance, self.1),
+            Direction::Up => Self::new(self.x, self.y - rhs.distance),
+            Direction::Right => Self::new(self.x + rhs.distance, self.y),
+            Direction::Down => Self::new(self.x, self.y + rhs.distance),
+            Direction::Left => Self::new(self.x - rhs.distance, self.y),
         }
     }
 }
 
 #[derive(Debug, PartialEq)]
-struct Polygon {
-    points: Vec<Point>,
+enum DigPlanError {
+    /// The plan doesn't lead back to where it started.
+    Open,
+    /// The instructions at these indices dig through some of the same ground.
+    Crossing(usize, usize),
 }
 
-#[derive(Debug, PartialEq)]
-struct PolygonGeometryError;
-
-impl Polygon {
-    fn area(&self) -> u64 {
-        let (a, b) = self
-            .points
-            .iter()
-            .enumerate()
-            .fold((0, 0), |(a, b), (ix, point)| {
-                let next = self.points[(ix + 1) % self.points.len()];
-                (a + (point.0 * next.1), b + (point.1 * next.0))
-            });
-        a.abs_diff(b) / 2
+impl fmt::Display for DigPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open => write!(f, "the dig plan doesn't lead back to the start"),
+            Self::Crossing(first, second) => write!(
+                f,
+                "the instructions on lines {} and {} cross",
+                first + 1,
+                second + 1
+            ),
+        }
     }
+}
 
-    fn circumference(&self) -> u64 {
-        let (_, circ) = self
-            .points
-            .iter()
-            .fold((Point(0, 0), 0), |(prev, circ), point| {
-                (*point, circ + point.distance(prev))
-            });
-        circ
+/// Checks that no two parts of the trench share any ground, other than where each instruction
+/// starts from the end of the one before (and the first from the end of the last). Trenches are
+/// horizontal or vertical lines, so two of them meet exactly when the rectangles they span
+/// overlap, and an instruction which turns right round digs back over the one before.
+///
+/// The instructions are given with their line indices, leaving out any which dig nowhere, so that
+/// the ones either side of them are checked as neighbours.
+fn check_crossings(
+    instructions: &[(usize, &Instruction)],
+    points: &[Point],
+) -> Result<(), DigPlanError> {
+    let trenches: Vec<(Point, Point)> = points
+        .iter()
+        .copied()
+        .zip(points.iter().copied().skip(1))
+        .collect();
+    let last = trenches.len().saturating_sub(1);
+
+    for (first, (from, to)) in trenches.iter().enumerate() {
+        for (second, (other_from, other_to)) in trenches.iter().enumerate().skip(first + 1) {
+            let crosses = if second == first + 1 || (first == 0 && second == last) {
+                let (before, after) = if second == first + 1 {
+                    (instructions[first].1, instructions[second].1)
+                } else {
+                    (instructions[second].1, instructions[first].1)
+                };
+                before.direction.is_opposite(&after.direction)
+            } else {
+                from.x.min(to.x) <= other_from.x.max(other_to.x)
+                    && other_from.x.min(other_to.x) <= from.x.max(to.x)
+                    && from.y.min(to.y) <= other_from.y.max(other_to.y)
+                    && other_from.y.min(other_to.y) <= from.y.max(to.y)
+            };
+            if crosses {
+                return Err(DigPlanError::Crossing(
+                    instructions[first].0,
+                    instructions[second].0,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The outline of the trench dug by following the instructions from the origin, which must lead
+/// back to the origin without crossing itself.
+fn trench(instructions: &[Instruction]) -> Result<Polygon, DigPlanError> {
+    let digs: Vec<(usize, &Instruction)> = instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| instruction.distance != 0)
+        .collect();
+
+    let mut points = vec![Point::new(0, 0)];
+    let mut point = Point::new(0, 0);
+
+    for (_, instruction) in &digs {
+        point = point + *instruction;
+        points.push(point);
     }
 
-    fn area_including_circumference(&self) -> u64 {
-        self.area() + (self.circumference() / 2 + 1)
+    if point != Point::new(0, 0) {
+        return Err(DigPlanError::Open);
     }
+    check_crossings(&digs, &points)?;
+    Polygon::from_closed_path(points).map_err(|_| DigPlanError::Open)
+}
 
-    fn from_instructions(instructions: &[Instruction]) -> Result<Self, PolygonGeometryError> {
-        let mut points = Vec::new();
-        let mut point = Point(0, 0);
+/// The coordinates to draw along one axis: one line for each coordinate used by a vertex, one
+/// line for each run of coordinates between them, and a margin either side.
+fn compressed_axis(values: impl Iterator<Item = i64>) -> Vec<i64> {
+    let mut values: Vec<i64> = values.collect();
+    values.sort_unstable();
+    values.dedup();
 
-        for instruction in instructions {
-            point = point + instruction;
-            points.push(point);
+    let mut axis = Vec::new();
+    if let Some(first) = values.first() {
+        axis.push(first - 1);
+    }
+    for (ix, value) in values.iter().enumerate() {
+        axis.push(*value);
+        if values.get(ix + 1).is_none_or(|next| next - value > 1) {
+            axis.push(value + 1);
         }
+    }
+    axis
+}
 
-        if point == Point(0, 0) {
-            Ok(Self { points })
-        } else {
-            Err(PolygonGeometryError)
+/// Works out where each point of a grid lies relative to a rectilinear polygon, one row at a time
+/// rather than casting a ray from every point. A point is on the boundary if an edge runs through
+/// it, and otherwise inside if an odd number of vertical edges cross its row to its left (each
+/// edge including its top end but not its bottom, so that a row through a corner counts once).
+fn scan_containment(polygon: &Polygon, cols: &[i64], rows: &[i64]) -> Vec<Containment> {
+    let mut cells = Vec::with_capacity(cols.len() * rows.len());
+
+    for &y in rows {
+        let mut boundary = vec![false; cols.len()];
+        let mut crossings = Vec::new();
+        for (from, to) in polygon.edges() {
+            let (min_y, max_y) = (from.y.min(to.y), from.y.max(to.y));
+            if min_y <= y && y <= max_y {
+                let start = cols.partition_point(|x| *x < from.x.min(to.x));
+                let end = cols.partition_point(|x| *x <= from.x.max(to.x));
+                boundary[start..end].fill(true);
+            }
+            if from.x == to.x && min_y <= y && y < max_y {
+                crossings.push(from.x);
+            }
+        }
+        crossings.sort_unstable();
+
+        let mut crossed = 0;
+        for (col, &x) in cols.iter().enumerate() {
+            while crossings.get(crossed).is_some_and(|edge| *edge < x) {
+                crossed += 1;
+            }
+            cells.push(if boundary[col] {
+                Containment::Boundary
+            } else if crossed % 2 == 1 {
+                Containment::Inside
+            } else {
+                Containment::Outside
+            });
         }
     }
+
+    cells
+}
+
+/// Draws the lagoon with its coordinates compressed, so that each row and column is either a
+/// coordinate used by a corner of the trench or stands in for all the coordinates between two of
+/// them. The start is marked `S`.
+fn lagoon_frame(polygon: &Polygon) -> Frame {
+    let cols = compressed_axis(polygon.vertices().iter().map(|point| point.x));
+    let rows = compressed_axis(polygon.vertices().iter().map(|point| point.y));
+    let cells = scan_containment(polygon, &cols, &rows);
+
+    Frame::from_fn(cols.len(), rows.len(), |row, col| {
+        let point = Point::new(cols[col], rows[row]);
+        match cells[(row * cols.len()) + col] {
+            _ if point == Point::new(0, 0) => Cell::new('S', Style::fg(Colour::YELLOW).bold()),
+            Containment::Boundary => Cell::new('#', Style::fg(Colour::ORANGE).bold()),
+            Containment::Inside => Cell::new('~', Style::fg(Colour::RED).on(Colour::RED)),
+            Containment::Outside => Cell::new('.', Style::fg(Colour::GREY)),
+        }
+    })
+}
+
+/// The number of cubic metres of lava the lagoon holds: the trench itself and everything inside.
+fn lagoon_size(instructions: &[Instruction]) -> Option<u64> {
+    let polygon = trench(instructions).ok()?;
+    polygon
+        .lattice_points()
+        .ok()
+        .and_then(|size| u64::try_from(size).ok())
 }
 
 fn read_instructions(
@@ -179,8 +325,7 @@ pub fn part_one(input: &str) -> Option<u64> {
     let Ok(instructions) = read_instructions(input, false) else {
         return None;
     };
-    Polygon::from_instructions(&instructions)
-        .map_or(None, |polygon| Some(polygon.area_including_circumference()))
+    lagoon_size(&instructions)
 }
 
 #[must_use]
@@ -188,8 +333,47 @@ pub fn part_two(input: &str) -> Option<u64> {
     let Ok(instructions) = read_instructions(input, true) else {
         return None;
     };
-    Polygon::from_instructions(&instructions)
-        .map_or(None, |polygon| Some(polygon.area_including_circumference()))
+    lagoon_size(&instructions)
+}
+
+/// With `--visualise`, draws the lagoon for both parts with their coordinates compressed, or
+/// explains what is wrong with the dig plan. With `--export <file>`, saves part two's drawing as
+/// an image.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{flag_value, has_flag};
+
+    let export = flag_value("--export");
+    if !has_flag("--visualise") && export.is_none() {
+        return;
+    }
+
+    for (part, hex) in [(1, false), (2, true)] {
+        let Ok(instructions) = read_instructions(input, hex) else {
+            eprintln!("Failed to read dig plan for part {part}");
+            continue;
+        };
+        let polygon = match trench(&instructions) {
+            Ok(polygon) => polygon,
+            Err(err @ DigPlanError::Crossing(first, second)) => {
+                let (first, second) = (&instructions[first], &instructions[second]);
+                eprintln!("Part {part}: {err} ({first} and {second})");
+                continue;
+            }
+            Err(err) => {
+                eprintln!("Part {part}: {err}");
+                continue;
+            }
+        };
+
+        let frame = lagoon_frame(&polygon);
+        if has_flag("--visualise") {
+            println!("Part {part}:");
+            print!("{frame}");
+        }
+        if let (2, Some(path)) = (part, &export) {
+            image::export(&frame, path);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -258,24 +442,22 @@ mod tests {
     }
 
     fn example_polygon() -> Polygon {
-        Polygon {
-            points: vec![
-                Point(6, 0),
-                Point(6, 5),
-                Point(4, 5),
-                Point(4, 7),
-                Point(6, 7),
-                Point(6, 9),
-                Point(1, 9),
-                Point(1, 7),
-                Point(0, 7),
-                Point(0, 5),
-                Point(2, 5),
-                Point(2, 2),
-                Point(0, 2),
-                Point(0, 0),
-            ],
-        }
+        Polygon::new(vec![
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Point::new(6, 5),
+            Point::new(4, 5),
+            Point::new(4, 7),
+            Point::new(6, 7),
+            Point::new(6, 9),
+            Point::new(1, 9),
+            Point::new(1, 7),
+            Point::new(0, 7),
+            Point::new(0, 5),
+            Point::new(2, 5),
+            Point::new(2, 2),
+            Point::new(0, 2),
+        ])
     }
 
     #[test]
@@ -297,10 +479,108 @@ mod tests {
     }
 
     #[test]
-    fn test_polygon_from_instructions() {
+    fn test_trench() {
+        assert_eq!(trench(&example_instructions()), Ok(example_polygon()));
+
+        let mut instructions = example_instructions();
+        instructions.pop();
+        assert_eq!(trench(&instructions), Err(DigPlanError::Open));
+    }
+
+    fn plan(input: &str) -> Vec<Instruction> {
+        read_instructions(input, false).unwrap()
+    }
+
+    #[test]
+    fn test_crossings() {
+        // a figure of eight, where the fourth instruction digs across the first.
+        let instructions = plan("R 4\nD 2\nL 2\nU 4\nL 2\nD 2");
+        assert_eq!(trench(&instructions), Err(DigPlanError::Crossing(0, 3)));
+        assert_eq!(
+            DigPlanError::Crossing(0, 3).to_string(),
+            "the instructions on lines 1 and 4 cross"
+        );
+        assert_eq!(part_one("R 4\nD 2\nL 2\nU 4\nL 2\nD 2"), None);
+
+        // turning right round digs back along the same trench.
+        let instructions = plan("R 4\nL 2\nD 1\nL 2\nU 1");
+        assert_eq!(trench(&instructions), Err(DigPlanError::Crossing(0, 1)));
+        let instructions = plan("U 1\nR 2\nU 1\nL 2\nD 2");
+        assert_eq!(trench(&instructions), Err(DigPlanError::Crossing(0, 4)));
+
+        // touching another trench at a corner counts as crossing it.
+        let instructions = plan("R 2\nD 1\nR 2\nD 1\nL 2\nU 1\nL 2\nU 1");
+        assert_eq!(trench(&instructions), Err(DigPlanError::Crossing(1, 5)));
+
+        // instructions which dig nowhere are left out, and don't hide a turn right round.
+        let instructions = plan("R 2\nR 0\nD 2\nL 2\nU 0\nU 2");
+        assert_eq!(trench(&instructions), trench(&plan("R 2\nD 2\nL 2\nU 2")));
+        assert_eq!(part_one("R 2\nR 0\nD 2\nL 2\nU 0\nU 2"), Some(9));
+        let instructions = plan("R 4\nU 0\nL 2\nD 1\nL 2\nU 1");
+        assert_eq!(trench(&instructions), Err(DigPlanError::Crossing(0, 2)));
+
+        // the last instruction meets the first at the start.
+        assert!(trench(&plan("R 2\nD 2\nL 2\nU 2")).is_ok());
+        assert!(trench(&example_instructions()).is_ok());
+    }
+
+    #[test]
+    fn test_instruction_display() {
+        let instructions = example_instructions();
+        assert_eq!(instructions[0].to_string(), "R 6");
+        assert_eq!(instructions[13].to_string(), "U 2");
+    }
+
+    #[test]
+    fn test_compressed_axis() {
+        assert_eq!(
+            compressed_axis([0, 6, 6, 2, 1].into_iter()),
+            vec![-1, 0, 1, 2, 3, 6, 7]
+        );
+        assert_eq!(compressed_axis(std::iter::empty()), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_scan_containment() {
+        for polygon in [
+            example_polygon(),
+            trench(&plan("R 2\nD 1\nR 2\nD 2\nL 4\nU 3")).unwrap(),
+        ] {
+            let cols: Vec<i64> = (-2..10).collect();
+            let rows: Vec<i64> = (-2..12).collect();
+            let cells = scan_containment(&polygon, &cols, &rows);
+            for (row, y) in rows.iter().enumerate() {
+                for (col, x) in cols.iter().enumerate() {
+                    let point = Point::new(*x, *y);
+                    assert_eq!(
+                        cells[(row * cols.len()) + col],
+                        polygon.contains(point),
+                        "{point:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lagoon_frame() {
+        let frame = lagoon_frame(&example_polygon());
         assert_eq!(
-            Polygon::from_instructions(&example_instructions()),
-            Ok(example_polygon()),
+            frame.plain(),
+            [
+                ".........\n",
+                ".S######.\n",
+                ".#~~~~~#.\n",
+                ".###~~~#.\n",
+                "...#~~~#.\n",
+                ".###~###.\n",
+                ".#~~~#...\n",
+                ".##~~###.\n",
+                "..#~~~~#.\n",
+                "..######.\n",
+                ".........\n",
+            ]
+            .concat()
         );
     }
 
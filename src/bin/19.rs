@@ -1,3 +1,4 @@
+use advent_of_code::intervals::Interval;
 use std::cmp::Ordering;
 use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
@@ -93,52 +94,29 @@ impl Part {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-struct NumberRange(u32, u32);
-
-impl NumberRange {
-    const fn range(self) -> u32 {
-        1 + self.1 - self.0
-    }
-
-    const fn split(self, comparison: Ordering, comparator: u32) -> (Option<Self>, Option<Self>) {
-        let mut split: Option<Self> = None;
-        let mut retain: Option<Self> = None;
-
-        match comparison {
-            Ordering::Greater => {
-                if comparator >= self.1 {
-                    retain = Some(self);
-                } else if comparator < self.0 {
-                    split = Some(self);
-                } else {
-                    retain = Some(Self(self.0, comparator));
-                    split = Some(Self(comparator + 1, self.1));
-                }
-            }
-            Ordering::Less => {
-                if comparator > self.1 {
-                    split = Some(self);
-                } else if comparator <= self.0 {
-                    retain = Some(self);
-                } else {
-                    retain = Some(Self(comparator, self.1));
-                    split = Some(Self(self.0, comparator.saturating_sub(1)));
-                }
-            }
-            Ordering::Equal => (),
+/// Splits `range` into the values which satisfy the comparison (and so are sent on to another
+/// workflow) and those which don't (and so are retained for the next step).
+fn split_range(
+    range: Interval<u32>,
+    comparison: Ordering,
+    comparator: u32,
+) -> (Option<Interval<u32>>, Option<Interval<u32>>) {
+    match comparison {
+        Ordering::Greater => {
+            let (below, above) = range.split_at(comparator + 1);
+            (above, below)
         }
-
-        (split, retain)
+        Ordering::Less => range.split_at(comparator),
+        Ordering::Equal => (None, None),
     }
 }
 
 #[derive(Debug, PartialEq)]
 struct Tesseract {
-    x: NumberRange,
-    m: NumberRange,
-    a: NumberRange,
-    s: NumberRange,
+    x: Interval<u32>,
+    m: Interval<u32>,
+    a: Interval<u32>,
+    s: Interval<u32>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -150,14 +128,14 @@ struct PossibilityState {
 impl Tesseract {
     const fn initial() -> Self {
         Self {
-            x: NumberRange(1, 4000),
-            m: NumberRange(1, 4000),
-            a: NumberRange(1, 4000),
-            s: NumberRange(1, 4000),
+            x: Interval::new(1, 4001),
+            m: Interval::new(1, 4001),
+            a: Interval::new(1, 4001),
+            s: Interval::new(1, 4001),
         }
     }
 
-    const fn get(&self, property: &Property) -> NumberRange {
+    const fn get(&self, property: &Property) -> Interval<u32> {
         match property {
             Property::X => self.x,
             Property::M => self.m,
@@ -166,7 +144,7 @@ impl Tesseract {
         }
     }
 
-    const fn with_property_replaced(&self, property: &Property, range: NumberRange) -> Self {
+    const fn with_property_replaced(&self, property: &Property, range: Interval<u32>) -> Self {
         match property {
             Property::X => Self { x: range, ..*self },
             Property::M => Self { m: range, ..*self },
@@ -177,7 +155,7 @@ impl Tesseract {
 
     fn split(&self, workstep: &WorkStep) -> (Option<PossibilityState>, Option<Self>) {
         let range = self.get(&workstep.property);
-        let (split, retain) = range.split(workstep.comparison, workstep.comparator);
+        let (split, retain) = split_range(range, workstep.comparison, workstep.comparator);
 
         let split = split.map(|rg| PossibilityState {
             tesseract: self.with_property_replaced(&workstep.property, rg),
@@ -189,10 +167,10 @@ impl Tesseract {
     }
 
     fn volume(&self) -> u64 {
-        let x = u64::from(self.x.range());
-        let m = u64::from(self.m.range());
-        let a = u64::from(self.a.range());
-        let s = u64::from(self.s.range());
+        let x = u64::from(self.x.length());
+        let m = u64::from(self.m.length());
+        let a = u64::from(self.a.length());
+        let s = u64::from(self.s.length());
         x * m * a * s
     }
 }
@@ -852,33 +830,39 @@ mod tests {
     }
 
     #[test]
-    fn test_number_range_split() {
-        let range = NumberRange(1, 4000);
+    fn test_split_range() {
+        let range = Interval::new(1, 4001);
         assert_eq!(
-            range.split(Ordering::Less, 2000),
-            (Some(NumberRange(1, 1999)), Some(NumberRange(2000, 4000))),
+            split_range(range, Ordering::Less, 2000),
+            (
+                Some(Interval::new(1, 2000)),
+                Some(Interval::new(2000, 4001))
+            ),
         );
         assert_eq!(
-            range.split(Ordering::Greater, 3707),
-            (Some(NumberRange(3708, 4000)), Some(NumberRange(1, 3707))),
+            split_range(range, Ordering::Greater, 3707),
+            (
+                Some(Interval::new(3708, 4001)),
+                Some(Interval::new(1, 3708))
+            ),
         );
         assert_eq!(
-            range.split(Ordering::Less, 5000),
-            (Some(NumberRange(1, 4000)), None),
+            split_range(range, Ordering::Less, 5000),
+            (Some(Interval::new(1, 4001)), None),
         );
         assert_eq!(
-            range.split(Ordering::Greater, 4000),
-            (None, Some(NumberRange(1, 4000))),
+            split_range(range, Ordering::Greater, 4000),
+            (None, Some(Interval::new(1, 4001))),
         );
     }
 
     #[test]
     fn test_tesseract_volume() {
         let tesseract = Tesseract {
-            x: NumberRange(1, 200),
-            m: NumberRange(12, 17),
-            a: NumberRange(1, 100),
-            s: NumberRange(13, 672),
+            x: Interval::new(1, 201),
+            m: Interval::new(12, 18),
+            a: Interval::new(1, 101),
+            s: Interval::new(13, 673),
         };
         assert_eq!(tesseract.volume(), 79_200_000);
     }
@@ -886,10 +870,10 @@ mod tests {
     #[test]
     fn test_tesseract_split() {
         let tesseract = Tesseract {
-            x: NumberRange(1, 4000),
-            m: NumberRange(1, 4000),
-            a: NumberRange(1, 4000),
-            s: NumberRange(1, 4000),
+            x: Interval::new(1, 4001),
+            m: Interval::new(1, 4001),
+            a: Interval::new(1, 4001),
+            s: Interval::new(1, 4001),
         };
         let workstep = WorkStep {
             property: Property::A,
@@ -902,18 +886,18 @@ mod tests {
             (
                 Some(PossibilityState {
                     tesseract: Tesseract {
-                        x: NumberRange(1, 4000),
-                        m: NumberRange(1, 4000),
-                        a: NumberRange(2001, 4000),
-                        s: NumberRange(1, 4000),
+                        x: Interval::new(1, 4001),
+                        m: Interval::new(1, 4001),
+                        a: Interval::new(2001, 4001),
+                        s: Interval::new(1, 4001),
                     },
                     workflow_name: WorkflowName('A', 'B', 'C'),
                 }),
                 Some(Tesseract {
-                    x: NumberRange(1, 4000),
-                    m: NumberRange(1, 4000),
-                    a: NumberRange(1, 2000),
-                    s: NumberRange(1, 4000),
+                    x: Interval::new(1, 4001),
+                    m: Interval::new(1, 4001),
+                    a: Interval::new(1, 2001),
+                    s: Interval::new(1, 4001),
                 }),
             ),
         );
@@ -923,10 +907,10 @@ mod tests {
     fn test_process_tesserat() {
         let state = PossibilityState {
             tesseract: Tesseract {
-                x: NumberRange(1, 4000),
-                m: NumberRange(1, 4000),
-                a: NumberRange(1, 4000),
-                s: NumberRange(1, 4000),
+                x: Interval::new(1, 4001),
+                m: Interval::new(1, 4001),
+                a: Interval::new(1, 4001),
+                s: Interval::new(1, 4001),
             },
             workflow_name: WorkflowName('p', 'x', ' '),
         };
@@ -956,10 +940,10 @@ mod tests {
             queue.pop_front(),
             Some(PossibilityState {
                 tesseract: Tesseract {
-                    x: NumberRange(1, 4000),
-                    m: NumberRange(1, 4000),
-                    a: NumberRange(1, 2005),
-                    s: NumberRange(1, 4000),
+                    x: Interval::new(1, 4001),
+                    m: Interval::new(1, 4001),
+                    a: Interval::new(1, 2006),
+                    s: Interval::new(1, 4001),
                 },
                 workflow_name: WorkflowName('q', 'k', 'q'),
             }),
@@ -968,10 +952,10 @@ mod tests {
             queue.pop_front(),
             Some(PossibilityState {
                 tesseract: Tesseract {
-                    x: NumberRange(1, 4000),
-                    m: NumberRange(2091, 4000),
-                    a: NumberRange(2006, 4000),
-                    s: NumberRange(1, 4000),
+                    x: Interval::new(1, 4001),
+                    m: Interval::new(2091, 4001),
+                    a: Interval::new(2006, 4001),
+                    s: Interval::new(1, 4001),
                 },
                 workflow_name: WorkflowName('A', ' ', ' '),
             }),
@@ -980,10 +964,10 @@ mod tests {
             queue.pop_front(),
             Some(PossibilityState {
                 tesseract: Tesseract {
-                    x: NumberRange(1, 4000),
-                    m: NumberRange(1, 2090),
-                    a: NumberRange(2006, 4000),
-                    s: NumberRange(1, 4000),
+                    x: Interval::new(1, 4001),
+                    m: Interval::new(1, 2091),
+                    a: Interval::new(2006, 4001),
+                    s: Interval::new(1, 4001),
                 },
                 workflow_name: WorkflowName('r', 'f', 'g'),
             }),
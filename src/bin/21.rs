@@ -1,6 +1,9 @@
+use advent_of_code::image;
+use advent_of_code::render::{self, Cell, Colour, Frame, Style};
 use std::str::FromStr;
+use std::time::Duration;
 
-advent_of_code::solution!(21);
+advent_of_code::solution!(21, visualise);
 
 #[derive(Debug, PartialEq)]
 enum Direction {
@@ -145,6 +148,33 @@ impl Grid {
     }
 }
 
+impl Grid {
+    /// Frames showing the garden at the start and after each of the given number of steps.
+    fn step_frames(self, steps: usize) -> Vec<Frame> {
+        let mut frames = vec![self.frame()];
+        (0..steps).fold(self, |grid, _| {
+            let grid = grid.step();
+            frames.push(grid.frame());
+            grid
+        });
+        frames
+    }
+
+    /// Draws the garden in the same way as the puzzle description, with the plots that can be
+    /// reached highlighted.
+    fn frame(&self) -> Frame {
+        Frame::from_fn(
+            self.size,
+            self.spaces.len() / self.size,
+            |row, col| match self.spaces[(row * self.size) + col] {
+                Space::Empty => Cell::new('.', Style::fg(Colour::GREY)),
+                Space::Blocked => Cell::new('#', Style::fg(Colour::GREEN)),
+                Space::Occupied => Cell::new('O', Style::fg(Colour::YELLOW).bold()),
+            },
+        )
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct ParseGridError;
 
@@ -202,6 +232,29 @@ pub fn part_two(input: &str) -> Option<usize> {
     })
 }
 
+/// With `--visualise`, animates the elf's steps through the garden for part one, and with
+/// `--frames <directory>` saves those same frames there as numbered images.
+fn visualise(input: &str) {
+    use advent_of_code::template::runner::{flag_value, has_flag};
+
+    let frames_directory = flag_value("--frames");
+    if !has_flag("--visualise") && frames_directory.is_none() {
+        return;
+    }
+    let Ok(grid) = Grid::from_str(input) else {
+        eprintln!("Failed to read garden");
+        return;
+    };
+
+    let frames = grid.step_frames(64);
+    if let Some(directory) = frames_directory {
+        image::export_sequence(frames.clone(), &directory);
+    }
+    if has_flag("--visualise") {
+        render::animate(frames, Duration::from_millis(80));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +340,36 @@ mod tests {
         assert_eq!(one.step(), two);
     }
 
+    #[test]
+    fn test_grid_frame() {
+        assert_eq!(
+            example_grid_after_two_steps().frame().plain(),
+            [
+                "...........\n",
+                ".....###.#.\n",
+                ".###.##..#.\n",
+                "..#.#O..#..\n",
+                "....#.#....\n",
+                ".##O.O####.\n",
+                ".##.O#...#.\n",
+                ".......##..\n",
+                ".##.#.####.\n",
+                ".##..##.##.\n",
+                "...........\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_step_frames() {
+        let frames = example_grid().step_frames(3);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(frames[0], example_grid().frame());
+        assert_eq!(frames[2], example_grid_after_two_steps().frame());
+        assert_eq!(frames[3], example_grid_after_two_steps().step().frame());
+    }
+
     #[test]
     fn test_grid_reachable_in_steps() {
         let grid = example_grid();
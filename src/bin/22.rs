@@ -1,16 +1,64 @@
-use std::cmp::Ordering;
 use std::collections::BTreeSet;
+use std::fmt;
 
-advent_of_code::solution!(22);
+advent_of_code::solution!(22, report);
 
-const GRID_SIZE: usize = 10;
-const GRID_HEIGHT: usize = 310;
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Point {
+    x: usize,
+    y: usize,
+    z: usize,
+}
+
+impl Point {
+    const fn new(x: usize, y: usize, z: usize) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
 
-const fn cube(x: usize, y: usize, z: usize) -> usize {
-    (z * GRID_SIZE * GRID_SIZE) + (y * GRID_SIZE) + x
+/// A brick, given by the cubes at either end. Neither coordinate of `start` is greater than the
+/// same coordinate of `end`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Brick {
+    start: Point,
+    end: Point,
+}
+
+impl Brick {
+    const fn new(start: Point, end: Point) -> Self {
+        Self { start, end }
+    }
+
+    /// Every cube making up the brick.
+    fn cubes(&self) -> impl Iterator<Item = Point> {
+        let (start, end) = (self.start, self.end);
+        (start.z..=end.z).flat_map(move |z| {
+            (start.y..=end.y).flat_map(move |y| (start.x..=end.x).map(move |x| Point::new(x, y, z)))
+        })
+    }
+
+    /// The same brick, one cube lower.
+    const fn lowered(&self) -> Self {
+        Self {
+            start: Point::new(self.start.x, self.start.y, self.start.z - 1),
+            end: Point::new(self.end.x, self.end.y, self.end.z - 1),
+        }
+    }
+}
+
+impl fmt::Display for Brick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}~{}", self.start, self.end)
+    }
 }
 
-fn read_cube(text: &str) -> Option<(usize, usize, usize)> {
+fn read_cube(text: &str) -> Option<Point> {
     let mut x: Option<usize> = None;
     let mut y: Option<usize> = None;
     let mut z: Option<usize> = None;
@@ -25,35 +73,23 @@ fn read_cube(text: &str) -> Option<(usize, usize, usize)> {
         }
     }
 
-    let x = x?;
-    let y = y?;
-    let z = z?;
-    Some((x, y, z))
+    Some(Point::new(x?, y?, z?))
 }
 
-fn read_brick(line: &str) -> Option<Vec<usize>> {
+fn read_brick(line: &str) -> Option<Brick> {
     let (first, last) = line.split_once('~')?;
-    let (mut x, mut y, mut z) = read_cube(first)?;
-    let (last_x, last_y, last_z) = read_cube(last)?;
+    let start = read_cube(first)?;
+    let end = read_cube(last)?;
 
-    if (x > last_x) || (y > last_y) || (z > last_z) {
+    if (start.x > end.x) || (start.y > end.y) || (start.z > end.z) {
         return None;
     }
 
-    let mut brick = Vec::new();
-
-    while (x, y, z) != (last_x, last_y, last_z) {
-        brick.push(cube(x, y, z));
-        x += usize::from(x < last_x);
-        y += usize::from(y < last_y);
-        z += usize::from(z < last_z);
-    }
-    brick.push(cube(x, y, z));
-
-    Some(brick)
+    Some(Brick::new(start, end))
 }
 
-fn read_bricks(input: &str) -> Vec<Vec<usize>> {
+/// Reads the bricks, ordered from the lowest up.
+fn read_bricks(input: &str) -> Vec<Brick> {
     let mut bricks = Vec::new();
 
     for line in input.lines() {
@@ -62,69 +98,69 @@ fn read_bricks(input: &str) -> Vec<Vec<usize>> {
         }
     }
 
-    bricks.sort_unstable_by(
-        |a: &Vec<usize>, b: &Vec<usize>| match (a.first(), b.first()) {
-            (None, None) => Ordering::Equal,
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(a), Some(b)) => a.cmp(b),
-        },
-    );
+    bricks.sort_by_key(|brick| (brick.start.z, brick.start.y, brick.start.x));
 
     bricks
 }
 
+/// The space the bricks fall through, as a grid of cubes just big enough to hold them all,
+/// recording which brick (if any) fills each one.
 #[derive(Debug, PartialEq)]
-struct SupportGraph {
-    supporters: Vec<BTreeSet<usize>>,
-    supporting: Vec<BTreeSet<usize>>,
+struct Space {
+    width: usize,
+    depth: usize,
+    occupied: Vec<Option<usize>>,
 }
 
-impl SupportGraph {
-    fn removable_bricks(&self) -> usize {
-        // a brick is removable if all the bricks it supports have multiple supporters
-        self.supporting
-            .iter()
-            .map(|supporting| {
-                usize::from(
-                    supporting
-                        .iter()
-                        .all(|supported| self.supporters[*supported].len() > 1),
-                )
-            })
-            .sum()
+impl Space {
+    fn around(bricks: &[Brick]) -> Self {
+        let bound = |axis: fn(&Point) -> usize| {
+            bricks
+                .iter()
+                .map(|brick| axis(&brick.end) + 1)
+                .max()
+                .unwrap_or_default()
+        };
+        let width = bound(|point| point.x);
+        let depth = bound(|point| point.y);
+        let height = bound(|point| point.z);
+        Self {
+            width,
+            depth,
+            occupied: vec![None; width * depth * height],
+        }
     }
 
-    fn remove_brick(&self, ix: usize) -> usize {
-        let mut removed = BTreeSet::new();
-        let mut queue = Vec::new();
-        queue.push(ix);
+    const fn index(&self, point: Point) -> usize {
+        (point.z * self.width * self.depth) + (point.y * self.width) + point.x
+    }
 
-        while let Some(remove) = queue.pop() {
-            // record removal of the brick
-            removed.insert(remove);
+    fn get(&self, point: Point) -> Option<usize> {
+        self.occupied[self.index(point)]
+    }
 
-            // iterate bricks supported by the one removed, find those with no remaining
-            // supporters, and mark them for removal
-            for brick in &self.supporting[remove] {
-                if self.supporters[*brick]
-                    .difference(&removed)
-                    .next()
-                    .is_none()
-                {
-                    queue.push(*brick);
-                }
-            }
+    fn fill(&mut self, brick: &Brick, brick_ix: usize) {
+        for cube in brick.cubes() {
+            let index = self.index(cube);
+            self.occupied[index] = Some(brick_ix);
         }
-
-        removed.len().saturating_sub(1)
     }
 }
 
-impl From<Vec<Vec<usize>>> for SupportGraph {
-    fn from(bricks: Vec<Vec<usize>>) -> Self {
-        let mut occupied: Vec<Option<usize>> = vec![None; GRID_HEIGHT * GRID_SIZE * GRID_SIZE];
+/// The bricks once they have all fallen as far as they can, and which rest on which.
+#[derive(Debug, PartialEq)]
+struct Stack {
+    bricks: Vec<Brick>,
+    graph: SupportGraph,
+}
 
+impl Stack {
+    /// Lets the bricks fall, lowest first, until each rests on the ground (at `z = 0`) or on
+    /// other bricks.
+    fn settle(bricks: Vec<Brick>) -> Self {
+        let mut space = Space::around(&bricks);
+
+        let mut settled = Vec::new();
         let mut supporters = Vec::new();
         let mut supporting = Vec::new();
 
@@ -132,21 +168,9 @@ impl From<Vec<Vec<usize>>> for SupportGraph {
             let mut supported_by = BTreeSet::new();
 
             // Lower the brick as much as possible
-            loop {
-                let beneath: Vec<usize> = brick
-                    .iter()
-                    .filter_map(|cube| cube.checked_sub(GRID_SIZE * GRID_SIZE))
-                    .collect();
-                if beneath.len() < brick.len() {
-                    break;
-                }
-
-                supported_by.clear();
-                for pos in &beneath {
-                    if let Some(other_brick) = occupied[*pos] {
-                        supported_by.insert(other_brick);
-                    }
-                }
+            while brick.start.z > 1 {
+                let beneath = brick.lowered();
+                supported_by.extend(beneath.cubes().filter_map(|cube| space.get(cube)));
                 if !supported_by.is_empty() {
                     break;
                 }
@@ -154,9 +178,8 @@ impl From<Vec<Vec<usize>>> for SupportGraph {
             }
 
             // Record cube positions occupied by this brick
-            for cube in brick {
-                occupied[cube] = Some(brick_ix);
-            }
+            space.fill(&brick, brick_ix);
+            settled.push(brick);
 
             // Record supporters and create an empty supporting entry for later population
             supporters.push(supported_by);
@@ -171,9 +194,106 @@ impl From<Vec<Vec<usize>>> for SupportGraph {
         }
 
         Self {
-            supporters,
-            supporting,
+            bricks: settled,
+            graph: SupportGraph {
+                supporters,
+                supporting,
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct SupportGraph {
+    supporters: Vec<BTreeSet<usize>>,
+    supporting: Vec<BTreeSet<usize>>,
+}
+
+impl SupportGraph {
+    fn removable_bricks(&self) -> usize {
+        // a brick is removable if all the bricks it supports have multiple supporters
+        self.supporting
+            .iter()
+            .map(|supporting| {
+                usize::from(
+                    supporting
+                        .iter()
+                        .all(|supported| self.supporters[*supported].len() > 1),
+                )
+            })
+            .sum()
+    }
+
+    /// The immediate dominator of each brick, treating the ground as the root: the highest brick
+    /// which every chain of support between the brick and the ground passes through, or `None`
+    /// if there isn't one. Removing a brick makes exactly the bricks it dominates fall.
+    ///
+    /// Bricks only rest on bricks which settled before them, so each brick's supporters already
+    /// have their dominators when it is reached, and the dominator is where the supporters'
+    /// chains of dominators meet.
+    fn dominators(&self) -> Vec<Option<usize>> {
+        let mut dominators: Vec<Option<usize>> = Vec::with_capacity(self.supporters.len());
+        let mut depths: Vec<usize> = Vec::with_capacity(self.supporters.len());
+        let depth = |depths: &[usize], brick: Option<usize>| brick.map_or(0, |ix| depths[ix]);
+
+        for supporters in &self.supporters {
+            let mut supporters = supporters.iter().copied().map(Some);
+            let mut dominator = supporters.next().flatten();
+            for mut other in supporters {
+                while dominator != other {
+                    if depth(&depths, dominator) > depth(&depths, other) {
+                        dominator = dominator.and_then(|ix| dominators[ix]);
+                    } else {
+                        other = other.and_then(|ix| dominators[ix]);
+                    }
+                }
+            }
+            depths.push(depth(&depths, dominator) + 1);
+            dominators.push(dominator);
+        }
+
+        dominators
+    }
+
+    /// How many other bricks would fall if each brick were removed.
+    fn falling_counts(&self) -> Vec<usize> {
+        let dominators = self.dominators();
+        let mut sizes = vec![1; dominators.len()];
+        for (brick, dominator) in dominators.iter().enumerate().rev() {
+            if let Some(dominator) = dominator {
+                sizes[*dominator] += sizes[brick];
+            }
+        }
+        sizes.into_iter().map(|size| size - 1).collect()
+    }
+
+    /// The bricks which would fall if each brick were removed.
+    fn falling(&self) -> Vec<Vec<usize>> {
+        let mut dominated = vec![Vec::new(); self.supporters.len()];
+        for (brick, dominator) in self.dominators().into_iter().enumerate() {
+            if let Some(dominator) = dominator {
+                dominated[dominator].push(brick);
+            }
         }
+
+        (0..dominated.len())
+            .map(|brick| {
+                let mut falling = Vec::new();
+                let mut queue = dominated[brick].clone();
+                while let Some(next) = queue.pop() {
+                    falling.push(next);
+                    queue.extend(&dominated[next]);
+                }
+                falling.sort_unstable();
+                falling
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Brick>> for SupportGraph {
+    fn from(bricks: Vec<Brick>) -> Self {
+        Stack::settle(bricks).graph
     }
 }
 
@@ -195,27 +315,51 @@ pub fn part_two(input: &str) -> Option<usize> {
         None
     } else {
         let graph = SupportGraph::from(bricks);
-        Some(
-            (0..graph.supporters.len())
-                .map(|ix| graph.remove_brick(ix))
-                .sum(),
-        )
+        Some(graph.falling_counts().into_iter().sum())
+    }
+}
+
+/// With `--report`, lists each brick (numbered from the lowest up) with where it comes to rest
+/// and which other bricks would fall if it were removed.
+fn report(input: &str) {
+    if !advent_of_code::template::runner::has_flag("--report") {
+        return;
+    }
+
+    let bricks = read_bricks(input);
+    let stack = Stack::settle(bricks.clone());
+    for (ix, (brick, falling)) in bricks.iter().zip(stack.graph.falling()).enumerate() {
+        print!("#{ix} {brick} -> {}: ", stack.bricks[ix]);
+        if falling.is_empty() {
+            println!("safe to remove");
+        } else {
+            let names: Vec<String> = falling.iter().map(|other| format!("#{other}")).collect();
+            println!("{} would fall ({})", falling.len(), names.join(", "));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::random::Lcg;
 
-    fn example_bricks() -> Vec<Vec<usize>> {
+    fn brick(start: (usize, usize, usize), end: (usize, usize, usize)) -> Brick {
+        Brick::new(
+            Point::new(start.0, start.1, start.2),
+            Point::new(end.0, end.1, end.2),
+        )
+    }
+
+    fn example_bricks() -> Vec<Brick> {
         vec![
-            vec![cube(1, 0, 1), cube(1, 1, 1), cube(1, 2, 1)],
-            vec![cube(0, 0, 2), cube(1, 0, 2), cube(2, 0, 2)],
-            vec![cube(0, 2, 3), cube(1, 2, 3), cube(2, 2, 3)],
-            vec![cube(0, 0, 4), cube(0, 1, 4), cube(0, 2, 4)],
-            vec![cube(2, 0, 5), cube(2, 1, 5), cube(2, 2, 5)],
-            vec![cube(0, 1, 6), cube(1, 1, 6), cube(2, 1, 6)],
-            vec![cube(1, 1, 8), cube(1, 1, 9)],
+            brick((1, 0, 1), (1, 2, 1)),
+            brick((0, 0, 2), (2, 0, 2)),
+            brick((0, 2, 3), (2, 2, 3)),
+            brick((0, 0, 4), (0, 2, 4)),
+            brick((2, 0, 5), (2, 2, 5)),
+            brick((0, 1, 6), (2, 1, 6)),
+            brick((1, 1, 8), (1, 1, 9)),
         ]
     }
 
@@ -310,12 +454,122 @@ mod tests {
     }
 
     #[test]
-    fn test_remove_brick() {
+    fn test_brick() {
+        let brick = read_brick("0,1,6~2,1,6").unwrap();
+        assert_eq!(brick, example_bricks()[5]);
+        assert_eq!(brick.to_string(), "0,1,6~2,1,6");
+        assert_eq!(
+            brick.cubes().collect::<Vec<_>>(),
+            vec![
+                Point::new(0, 1, 6),
+                Point::new(1, 1, 6),
+                Point::new(2, 1, 6)
+            ]
+        );
+        assert_eq!(brick.lowered(), read_brick("0,1,5~2,1,5").unwrap());
+        assert_eq!(read_brick("2,1,6~0,1,6"), None);
+        assert_eq!(read_brick("0,1~2,1"), None);
+    }
+
+    #[test]
+    fn test_settle() {
+        let stack = Stack::settle(example_bricks());
+        let settled: Vec<String> = stack.bricks.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            settled,
+            vec![
+                "1,0,1~1,2,1",
+                "0,0,2~2,0,2",
+                "0,2,2~2,2,2",
+                "0,0,3~0,2,3",
+                "2,0,3~2,2,3",
+                "0,1,4~2,1,4",
+                "1,1,5~1,1,6"
+            ]
+        );
+        assert_eq!(stack.graph, example_graph());
+    }
+
+    #[test]
+    fn test_dominators() {
+        assert_eq!(
+            example_graph().dominators(),
+            vec![None, Some(0), Some(0), Some(0), Some(0), Some(0), Some(5)]
+        );
+    }
+
+    #[test]
+    fn test_falling() {
         let graph = example_graph();
-        assert_eq!(graph.remove_brick(0), 6);
-        assert_eq!(graph.remove_brick(1), 0);
-        assert_eq!(graph.remove_brick(3), 0);
-        assert_eq!(graph.remove_brick(5), 1);
+        assert_eq!(graph.falling_counts(), vec![6, 0, 0, 0, 0, 1, 0]);
+        assert_eq!(
+            graph.falling(),
+            vec![
+                vec![1, 2, 3, 4, 5, 6],
+                vec![],
+                vec![],
+                vec![],
+                vec![],
+                vec![6],
+                vec![]
+            ]
+        );
+    }
+
+    /// Works out which bricks fall by repeatedly removing bricks left with no supporters.
+    fn remove_brick(graph: &SupportGraph, ix: usize) -> usize {
+        let mut removed = BTreeSet::new();
+        let mut queue = vec![ix];
+        while let Some(remove) = queue.pop() {
+            removed.insert(remove);
+            for brick in &graph.supporting[remove] {
+                if graph.supporters[*brick].is_subset(&removed) {
+                    queue.push(*brick);
+                }
+            }
+        }
+        removed.len() - 1
+    }
+
+    #[test]
+    fn test_falling_matches_removal() {
+        let mut lcg = Lcg::new(22);
+        let mut random = |limit| lcg.below(limit);
+        for _ in 0..20 {
+            let bricks: Vec<Brick> = (0..60)
+                .map(|z| {
+                    let (x, y, length) = (random(6), random(6), random(4));
+                    match random(3) {
+                        0 => brick((x, y, z + 1), (x + length, y, z + 1)),
+                        1 => brick((x, y, z + 1), (x, y + length, z + 1)),
+                        _ => brick((x, y, z + 1), (x, y, z + 1 + length)),
+                    }
+                })
+                .collect();
+            let graph = SupportGraph::from(bricks);
+            let expected: Vec<usize> = (0..60).map(|ix| remove_brick(&graph, ix)).collect();
+            assert_eq!(graph.falling_counts(), expected);
+            let falling: Vec<usize> = graph.falling().iter().map(Vec::len).collect();
+            assert_eq!(falling, expected);
+        }
+    }
+
+    #[test]
+    fn test_large_space() {
+        let input = "0,0,400~20,0,400\n5,0,500~5,30,500\n5,30,600~5,30,602\n0,0,1~0,0,1\n";
+        let stack = Stack::settle(read_bricks(input));
+        let settled: Vec<String> = stack.bricks.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            settled,
+            vec![
+                "0,0,1~0,0,1",
+                "0,0,2~20,0,2",
+                "5,0,3~5,30,3",
+                "5,30,4~5,30,6"
+            ]
+        );
+        assert_eq!(part_one(input), Some(1));
+        assert_eq!(part_two(input), Some(6));
     }
 
     #[test]
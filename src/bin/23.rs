@@ -1,9 +1,17 @@
+use advent_of_code::image;
+use advent_of_code::render::{self, Cell, Colour, Frame, Style};
 use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::panic;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::thread;
 
-advent_of_code::solution!(23);
+advent_of_code::solution!(23, extras);
 
 const GRID_SIZE: usize = 141;
+/// The most threads the longest hike is shared between, however many are asked for.
+const MAX_THREADS: usize = 256;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Direction {
@@ -52,42 +60,176 @@ impl GraphMappingState {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
-struct BitSet(usize);
+/// A set of small numbers, one bit each, with as many 64-bit words as it takes to hold them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct BitSet(Vec<u64>);
 
 impl BitSet {
-    const fn contains(self, pos: usize) -> bool {
-        let flag = 1 << pos;
-        self.0 & flag != 0
+    /// An empty set with room for the numbers below `size`.
+    fn new(size: usize) -> Self {
+        Self(vec![0; size.div_ceil(64)])
     }
 
-    const fn insert(self, pos: usize) -> Self {
-        let flag = 1 << pos;
-        Self(self.0 | flag)
+    fn contains(&self, pos: usize) -> bool {
+        self.0
+            .get(pos / 64)
+            .is_some_and(|word| word & (1 << (pos % 64)) != 0)
+    }
+
+    fn insert(&mut self, pos: usize) {
+        if pos / 64 >= self.0.len() {
+            self.0.resize(pos / 64 + 1, 0);
+        }
+        self.0[pos / 64] |= 1 << (pos % 64);
+    }
+
+    fn remove(&mut self, pos: usize) {
+        if let Some(word) = self.0.get_mut(pos / 64) {
+            *word &= !(1 << (pos % 64));
+        }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// A hike from the start to the finish: its length, and the points of interest it passes through
+/// in order (starting with the start).
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Hike {
+    steps: u32,
+    route: Vec<usize>,
+}
+
+/// A partly-explored hike: where it has got to, how far it has come and which points of interest
+/// it has passed through.
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct HikeState {
     position: usize,
     steps: u32,
     visited: BitSet,
+    route: Vec<usize>,
 }
 
 impl HikeState {
-    const fn new(position: usize) -> Self {
+    fn new(position: usize, size: usize) -> Self {
+        let mut visited = BitSet::new(size);
+        visited.insert(position);
         Self {
             position,
             steps: 0,
-            visited: BitSet(0).insert(position),
+            visited,
+            route: vec![position],
         }
     }
 
-    const fn visit(&self, position: usize, steps: u32) -> Self {
+    fn visit(&self, position: usize, steps: u32) -> Self {
+        let mut visited = self.visited.clone();
+        visited.insert(position);
+        let mut route = self.route.clone();
+        route.push(position);
         Self {
             position,
             steps: self.steps + steps,
-            visited: self.visited.insert(position),
+            visited,
+            route,
+        }
+    }
+}
+
+/// A depth-first search for the longest hike, which backtracks by updating a single set of
+/// visited points and gives up on any hike that can't beat the longest found so far (by this
+/// search or any other sharing the same `longest`).
+struct HikeSearch<'a> {
+    graph: &'a TrailGraph,
+    last_junction: Option<usize>,
+    visited: BitSet,
+    route: Vec<usize>,
+    longest: &'a AtomicU32,
+    found: Option<Hike>,
+    // scratch space for working out the bound, kept between calls to save reallocating it.
+    reached: BitSet,
+    longest_in: Vec<u32>,
+    stack: Vec<usize>,
+}
+
+impl<'a> HikeSearch<'a> {
+    fn new(graph: &'a TrailGraph, longest: &'a AtomicU32) -> Self {
+        let size = graph.nodes.len();
+        Self {
+            graph,
+            last_junction: graph.last_junction(),
+            visited: BitSet::new(size),
+            route: Vec::new(),
+            longest,
+            found: None,
+            reached: BitSet::new(size),
+            longest_in: vec![0; size],
+            stack: Vec::new(),
+        }
+    }
+
+    /// Carries on the search from a hike which has already been started.
+    fn resume(&mut self, state: &HikeState) -> Option<Hike> {
+        self.visited = state.visited.clone();
+        self.route.clone_from(&state.route);
+        self.explore(state.position, state.steps);
+        self.found.clone()
+    }
+
+    /// An upper bound on how much further a hike can go from `position`: the total of the longest
+    /// edge into each unvisited point which can still be reached. `None` if the finish can no
+    /// longer be reached at all.
+    fn remaining_bound(&mut self, position: usize) -> Option<u32> {
+        let graph = self.graph;
+        self.reached.0.fill(0);
+        self.reached.insert(position);
+        self.stack.clear();
+        self.stack.push(position);
+
+        let mut bound = 0;
+        while let Some(from) = self.stack.pop() {
+            for &(next, steps) in graph.moves(from, self.last_junction) {
+                if self.visited.contains(next) {
+                    continue;
+                }
+                if !self.reached.contains(next) {
+                    self.reached.insert(next);
+                    self.longest_in[next] = 0;
+                    self.stack.push(next);
+                }
+                if steps > self.longest_in[next] {
+                    bound += steps - self.longest_in[next];
+                    self.longest_in[next] = steps;
+                }
+            }
+        }
+
+        self.reached.contains(graph.finish).then_some(bound)
+    }
+
+    fn explore(&mut self, position: usize, steps: u32) {
+        if position == self.graph.finish {
+            let hike = Hike {
+                steps,
+                route: self.route.clone(),
+            };
+            self.found = self.found.take().max(Some(hike));
+            self.longest.fetch_max(steps, Ordering::Relaxed);
+            return;
+        }
+        match self.remaining_bound(position) {
+            Some(bound) if steps + bound >= self.longest.load(Ordering::Relaxed) => (),
+            _ => return,
+        }
+
+        let graph = self.graph;
+        for &(next, edge) in graph.moves(position, self.last_junction) {
+            if self.visited.contains(next) {
+                continue;
+            }
+            self.visited.insert(next);
+            self.route.push(next);
+            self.explore(next, steps + edge);
+            self.route.pop();
+            self.visited.remove(next);
         }
     }
 }
@@ -115,32 +257,171 @@ impl Trail {
 struct TrailGraph {
     start: usize,
     finish: usize,
-    nodes: Vec<Vec<Option<u32>>>,
+    /// The position of each point of interest on the map.
+    points: Vec<usize>,
+    /// For each point of interest, the points it leads to directly and the number of steps to
+    /// each.
+    nodes: Vec<Vec<(usize, u32)>>,
 }
 
 impl TrailGraph {
-    fn longest_hike(&self) -> Option<u32> {
-        let mut longest: Option<u32> = None;
-        let mut queue = Vec::new();
-        queue.push(HikeState::new(self.start));
-
-        while let Some(state) = queue.pop() {
-            if state.position == self.finish {
-                longest = longest.map_or(Some(state.steps), |steps| Some(steps.max(state.steps)));
-            } else if let Some(node) = self.nodes.get(state.position) {
-                for (position, steps) in node.iter().enumerate() {
-                    let Some(steps) = steps else {
-                        continue;
-                    };
-
-                    if !state.visited.contains(position) {
-                        queue.push(state.visit(position, *steps));
+    /// The only point leading to the finish, if there is just one. A hike which reaches it has to
+    /// go straight to the finish, as it would have no way back to it otherwise.
+    fn last_junction(&self) -> Option<usize> {
+        let mut before_finish = (0..self.nodes.len()).filter(|node| {
+            self.nodes[*node]
+                .iter()
+                .any(|(next, _)| *next == self.finish)
+        });
+        match (before_finish.next(), before_finish.next()) {
+            (Some(node), None) => Some(node),
+            _ => None,
+        }
+    }
+
+    /// The moves worth considering from `position`, given the graph's [`TrailGraph::last_junction`].
+    fn moves(&self, position: usize, last_junction: Option<usize>) -> &[(usize, u32)] {
+        let moves = &self.nodes[position];
+        if Some(position) == last_junction {
+            let finish = moves.iter().position(|(next, _)| *next == self.finish);
+            finish.map_or(moves, |ix| &moves[ix..=ix])
+        } else {
+            moves
+        }
+    }
+
+    /// The number of steps along the edge from `from` to `to`, if there is one.
+    fn edge(&self, from: usize, to: usize) -> Option<u32> {
+        self.nodes[from]
+            .iter()
+            .find(|(next, _)| *next == to)
+            .map(|(_, steps)| *steps)
+    }
+
+    /// The graph in Graphviz DOT format, with the edges of `hike` (if given) highlighted.
+    const fn dot<'a>(&'a self, hike: Option<&'a Hike>) -> Dot<'a> {
+        Dot { graph: self, hike }
+    }
+
+    fn longest_hike(&self) -> Option<Hike> {
+        let longest = AtomicU32::new(0);
+        let mut search = HikeSearch::new(self, &longest);
+        search.resume(&HikeState::new(self.start, self.nodes.len()))
+    }
+
+    /// Finds the longest hike using up to `threads` threads (at most [`MAX_THREADS`]), each
+    /// taking hikes which start with different choices at the first few junctions.
+    fn longest_hike_parallel(&self, threads: usize) -> Option<Hike> {
+        let threads = threads.clamp(1, MAX_THREADS);
+        let last_junction = self.last_junction();
+        let mut starts = vec![HikeState::new(self.start, self.nodes.len())];
+        let mut longest = None;
+        while !starts.is_empty() && starts.len() < threads * 8 {
+            let mut branches = Vec::new();
+            for state in &starts {
+                if state.position == self.finish {
+                    longest = longest.max(Some(Hike {
+                        steps: state.steps,
+                        route: state.route.clone(),
+                    }));
+                    continue;
+                }
+                for &(next, steps) in self.moves(state.position, last_junction) {
+                    if !state.visited.contains(next) {
+                        branches.push(state.visit(next, steps));
                     }
                 }
             }
+            starts = branches;
         }
 
-        longest
+        let shared = AtomicU32::new(longest.as_ref().map_or(0, |hike| hike.steps));
+        let next_start = AtomicUsize::new(0);
+        let found = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut search = HikeSearch::new(self, &shared);
+                        let mut found = None;
+                        while let Some(state) =
+                            starts.get(next_start.fetch_add(1, Ordering::Relaxed))
+                        {
+                            found = found.max(search.resume(state));
+                        }
+                        found
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                // a worker which panicked may have missed the longest hike, so pass the panic on.
+                .map(|worker| {
+                    worker
+                        .join()
+                        .unwrap_or_else(|err| panic::resume_unwind(err))
+                })
+                .max()
+                .flatten()
+        });
+
+        longest.max(found)
+    }
+}
+
+/// A [`TrailGraph`] written out in Graphviz DOT format. Pairs of edges which run both ways with
+/// the same length are drawn as a single line.
+struct Dot<'a> {
+    graph: &'a TrailGraph,
+    hike: Option<&'a Hike>,
+}
+
+impl Dot<'_> {
+    fn on_hike(&self, from: usize, to: usize) -> bool {
+        self.hike
+            .is_some_and(|hike| hike.route.windows(2).any(|pair| pair == [from, to]))
+    }
+}
+
+impl fmt::Display for Dot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let graph = self.graph;
+        writeln!(f, "digraph trails {{")?;
+        for (node, position) in graph.points.iter().enumerate() {
+            let (row, col) = (position / GRID_SIZE, position % GRID_SIZE);
+            let (name, shape) = match node {
+                _ if node == graph.start => ("start".to_string(), "box"),
+                _ if node == graph.finish => ("finish".to_string(), "box"),
+                _ => (node.to_string(), "ellipse"),
+            };
+            let colour = if self.hike.is_some_and(|hike| hike.route.contains(&node)) {
+                ", color=red"
+            } else {
+                ""
+            };
+            writeln!(
+                f,
+                "  {node} [label=\"{name}\\n({row}, {col})\", shape={shape}{colour}];"
+            )?;
+        }
+        for (from, edges) in graph.nodes.iter().enumerate() {
+            for &(to, steps) in edges {
+                let both_ways = graph.edge(to, from) == Some(steps);
+                if both_ways && to < from {
+                    continue;
+                }
+                let direction = if both_ways { ", dir=none" } else { "" };
+                let highlight = if self.on_hike(from, to) || (both_ways && self.on_hike(to, from)) {
+                    ", color=red, penwidth=2"
+                } else {
+                    ""
+                };
+                writeln!(
+                    f,
+                    "  {from} -> {to} [label=\"{steps}\"{direction}{highlight}];"
+                )?;
+            }
+        }
+        writeln!(f, "}}")
     }
 }
 
@@ -167,21 +448,9 @@ impl TrailMap {
         queue.push_back(GraphMappingState::new(start));
 
         while let Some(state) = queue.pop_front() {
-            let trail = self.trails[state.position];
-            let exits: Vec<usize> = COMPASS
-                .iter()
-                .filter_map(move |direction| {
-                    if !trail.can_exit_in_direction(*direction, ignore_slopes) {
-                        return None;
-                    }
-                    Self::step_in_direction(state.position, *direction)
-                        .filter(|pos| self.trails[*pos] != Trail::Forest)
-                })
-                .collect();
+            let exits = self.exits(state.position, ignore_slopes);
 
-            if state.position != state.source
-                && (exits.len() > 2 || trail == Trail::Start || trail == Trail::Finish)
-            {
+            if state.position != state.source && self.is_point_of_interest(state.position, &exits) {
                 // new point of interest found; record route, then initiate a new
                 // search from this POI if we haven't already
                 connections.insert((state.source, state.position, state.steps));
@@ -199,17 +468,17 @@ impl TrailMap {
             }
         }
 
+        poi.sort_unstable();
         let mut graph = TrailGraph {
             start: 0,
             finish: 0,
+            points: poi.clone(),
             nodes: Vec::new(),
         };
         let mut poi_indices = BTreeMap::new();
-        poi.sort_unstable();
         for (ix, node) in poi.iter().enumerate() {
             poi_indices.insert(node, ix);
-            let node = vec![None; poi.len()];
-            graph.nodes.push(node);
+            graph.nodes.push(Vec::new());
         }
 
         if let Some(start) = poi_indices.get(&start) {
@@ -226,12 +495,113 @@ impl TrailMap {
             let Some(destination) = poi_indices.get(&destination) else {
                 continue;
             };
-            graph.nodes[*origin][*destination] = Some(steps);
+            // keep only the longest route between each pair of points.
+            let edges = &mut graph.nodes[*origin];
+            match edges.iter_mut().find(|(next, _)| next == destination) {
+                Some(edge) => edge.1 = edge.1.max(steps),
+                None => edges.push((*destination, steps)),
+            }
         }
 
         graph
     }
 
+    /// The positions which can be walked to from `position`.
+    fn exits(&self, position: usize, ignore_slopes: bool) -> Vec<usize> {
+        let trail = self.trails[position];
+        COMPASS
+            .iter()
+            .filter_map(move |direction| {
+                if !trail.can_exit_in_direction(*direction, ignore_slopes) {
+                    return None;
+                }
+                Self::step_in_direction(position, *direction)
+                    .filter(|pos| self.trails[*pos] != Trail::Forest)
+            })
+            .collect()
+    }
+
+    fn is_point_of_interest(&self, position: usize, exits: &[usize]) -> bool {
+        let trail = self.trails[position];
+        exits.len() > 2 || trail == Trail::Start || trail == Trail::Finish
+    }
+
+    /// The positions along the trail from one point of interest to another which takes `steps`
+    /// steps (not including `from`, but including `to`).
+    fn corridor(
+        &self,
+        from: usize,
+        to: usize,
+        steps: u32,
+        ignore_slopes: bool,
+    ) -> Option<Vec<usize>> {
+        self.exits(from, ignore_slopes)
+            .into_iter()
+            .find_map(|first| {
+                let mut path = vec![first];
+                let mut position = first;
+                loop {
+                    let exits = self.exits(position, ignore_slopes);
+                    if self.is_point_of_interest(position, &exits) {
+                        let length = u32::try_from(path.len()).ok()?;
+                        return (position == to && length == steps).then_some(path);
+                    }
+                    position = exits
+                        .into_iter()
+                        .find(|next| *next != from && !path.contains(next))?;
+                    path.push(position);
+                }
+            })
+    }
+
+    /// Draws the top-left `rows` by `cols` part of the map, with the hike marked out as in the
+    /// puzzle description and the points of interest highlighted.
+    fn hike_frame(
+        &self,
+        graph: &TrailGraph,
+        hike: &Hike,
+        ignore_slopes: bool,
+        rows: usize,
+        cols: usize,
+    ) -> Frame {
+        let mut on_hike = vec![false; GRID_SIZE * GRID_SIZE];
+        for pair in hike.route.windows(2) {
+            let (from, to) = (graph.points[pair[0]], graph.points[pair[1]]);
+            let steps = graph.edge(pair[0], pair[1]).unwrap_or_default();
+            for position in self
+                .corridor(from, to, steps, ignore_slopes)
+                .unwrap_or_default()
+            {
+                on_hike[position] = true;
+            }
+        }
+
+        let mut frame = Frame::from_fn(cols, rows, |row, col| {
+            let position = (row * GRID_SIZE) + col;
+            match self.trails[position] {
+                _ if on_hike[position] => Cell::new('O', Style::fg(Colour::YELLOW).bold()),
+                Trail::Start => Cell::new('S', Style::fg(Colour::YELLOW).bold()),
+                Trail::Forest => Cell::new('#', Style::fg(Colour::GREEN)),
+                Trail::Slope(Direction::East) => Cell::new('>', Style::fg(Colour::GREY)),
+                Trail::Slope(Direction::South) => Cell::new('v', Style::fg(Colour::GREY)),
+                Trail::Slope(Direction::North) => Cell::new('^', Style::fg(Colour::GREY)),
+                Trail::Slope(Direction::West) => Cell::new('<', Style::fg(Colour::GREY)),
+                Trail::Empty | Trail::Finish => Cell::new('.', Style::fg(Colour::GREY)),
+            }
+        });
+        for position in &graph.points {
+            let (row, col) = (position / GRID_SIZE, position % GRID_SIZE);
+            if let Some(cell) = frame.get(row, col).copied() {
+                frame.set(
+                    row,
+                    col,
+                    Cell::new(cell.symbol, cell.style.on(Colour::BLUE)),
+                );
+            }
+        }
+        frame
+    }
+
     const fn step_in_direction(pos: usize, direction: Direction) -> Option<usize> {
         let row = pos / GRID_SIZE;
         let col = pos % GRID_SIZE;
@@ -312,7 +682,7 @@ impl FromStr for TrailMap {
 pub fn part_one(input: &str) -> Option<u32> {
     TrailMap::from_str(input).map_or(None, |trail_map| {
         let graph = trail_map.graph(false);
-        graph.longest_hike()
+        graph.longest_hike().map(|hike| hike.steps)
     })
 }
 
@@ -320,13 +690,82 @@ pub fn part_one(input: &str) -> Option<u32> {
 pub fn part_two(input: &str) -> Option<u32> {
     TrailMap::from_str(input).map_or(None, |trail_map| {
         let graph = trail_map.graph(true);
-        graph.longest_hike()
+        graph.longest_hike().map(|hike| hike.steps)
     })
 }
 
+/// With `--threads <n>`, also finds the longest hikes using `n` threads. With `--bench`, compares
+/// the single-threaded search against one using every available thread. With `--dot`, prints the
+/// graph of points of interest for each part in DOT format, with the longest hike highlighted.
+/// With `--visualise`, draws each part's longest hike over the map, and with `--export <file>`
+/// saves part two's as an image.
+fn extras(input: &str) {
+    use advent_of_code::template::runner::{benchmark, flag_value, has_flag};
+
+    let threads = flag_value("--threads").and_then(|threads| threads.parse::<usize>().ok());
+    let export = flag_value("--export");
+    if threads.is_none()
+        && export.is_none()
+        && !has_flag("--bench")
+        && !has_flag("--dot")
+        && !has_flag("--visualise")
+    {
+        return;
+    }
+    let Ok(trail_map) = TrailMap::from_str(input) else {
+        eprintln!("Failed to read trail map");
+        return;
+    };
+
+    let (rows, cols) = render::dimensions(input);
+    for (part, ignore_slopes) in [(1, false), (2, true)] {
+        let graph = trail_map.graph(ignore_slopes);
+        if let Some(threads) = threads {
+            match graph.longest_hike_parallel(threads) {
+                Some(hike) => println!("Part {part} with {threads} threads: {}", hike.steps),
+                None => println!("Part {part} with {threads} threads: no hike"),
+            }
+        }
+        if has_flag("--bench") {
+            let available = thread::available_parallelism().map_or(1, usize::from);
+            benchmark(
+                &format!("Part {part} single-threaded"),
+                TrailGraph::longest_hike,
+                &graph,
+            );
+            benchmark(
+                &format!("Part {part} with {available} threads"),
+                |graph: &TrailGraph| graph.longest_hike_parallel(available),
+                &graph,
+            );
+        }
+
+        let exporting = part == 2 && export.is_some();
+        if !has_flag("--dot") && !has_flag("--visualise") && !exporting {
+            continue;
+        }
+        let hike = graph.longest_hike();
+        if has_flag("--dot") {
+            println!("// Part {part}");
+            print!("{}", graph.dot(hike.as_ref()));
+        }
+        if let Some(hike) = hike {
+            let frame = trail_map.hike_frame(&graph, &hike, ignore_slopes, rows, cols);
+            if has_flag("--visualise") {
+                println!("Part {part} ({} steps):", hike.steps);
+                print!("{frame}");
+            }
+            if let (2, Some(path)) = (part, &export) {
+                image::export(&frame, path);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::random::Lcg;
 
     fn position(row: usize, col: usize) -> usize {
         (row * GRID_SIZE) + col
@@ -501,23 +940,27 @@ mod tests {
         TrailGraph {
             start: 0,
             finish: 8,
+            points: vec![1, 434, 708, 1572, 1838, 1846, 2692, 2698, 3123],
             nodes: vec![
-                vec![None, None, Some(15), None, None, None, None, None, None],
-                vec![None, None, None, Some(30), None, Some(24), None, None, None],
-                vec![None, Some(22), None, None, Some(22), None, None, None, None],
-                vec![None, None, None, None, None, None, None, Some(10), None],
-                vec![None, None, None, None, None, Some(12), Some(38), None, None],
-                vec![None, None, None, Some(18), None, None, Some(10), None, None],
-                vec![None, None, None, None, None, None, None, Some(10), None],
-                vec![None, None, None, None, None, None, None, None, Some(5)],
-                vec![None, None, None, None, None, None, None, None, None],
+                vec![(2, 15)],
+                vec![(3, 30), (5, 24)],
+                vec![(1, 22), (4, 22)],
+                vec![(7, 10)],
+                vec![(5, 12), (6, 38)],
+                vec![(3, 18), (6, 10)],
+                vec![(7, 10)],
+                vec![(8, 5)],
+                vec![],
             ],
         }
     }
 
     #[test]
     fn test_bit_set() {
-        let bs = BitSet(11);
+        let mut bs = BitSet::new(70);
+        bs.insert(0);
+        bs.insert(1);
+        bs.insert(3);
 
         assert_eq!(bs.contains(0), true);
         assert_eq!(bs.contains(1), true);
@@ -525,8 +968,19 @@ mod tests {
         assert_eq!(bs.contains(3), true);
         assert_eq!(bs.contains(4), false);
 
-        assert_eq!(bs.insert(2), BitSet(15));
-        assert_eq!(bs.insert(4), BitSet(27));
+        bs.insert(69);
+        assert!(bs.contains(69));
+        assert!(!bs.contains(5));
+        assert!(!bs.contains(1000));
+
+        bs.remove(1);
+        bs.remove(1000);
+        assert!(!bs.contains(1));
+        assert_eq!(bs, BitSet(vec![9, 1 << 5]));
+
+        bs.insert(130);
+        assert!(bs.contains(130));
+        assert_eq!(bs.0.len(), 3);
     }
 
     #[test]
@@ -553,86 +1007,17 @@ mod tests {
         TrailGraph {
             start: 0,
             finish: 8,
+            points: vec![1, 434, 708, 1572, 1838, 1846, 2692, 2698, 3123],
             nodes: vec![
-                vec![None, None, Some(15), None, None, None, None, None, None],
-                vec![
-                    None,
-                    None,
-                    Some(22),
-                    Some(30),
-                    None,
-                    Some(24),
-                    None,
-                    None,
-                    None,
-                ],
-                vec![
-                    Some(15),
-                    Some(22),
-                    None,
-                    None,
-                    Some(22),
-                    None,
-                    None,
-                    None,
-                    None,
-                ],
-                vec![
-                    None,
-                    Some(30),
-                    None,
-                    None,
-                    None,
-                    Some(18),
-                    None,
-                    Some(10),
-                    None,
-                ],
-                vec![
-                    None,
-                    None,
-                    Some(22),
-                    None,
-                    None,
-                    Some(12),
-                    Some(38),
-                    None,
-                    None,
-                ],
-                vec![
-                    None,
-                    Some(24),
-                    None,
-                    Some(18),
-                    Some(12),
-                    None,
-                    Some(10),
-                    None,
-                    None,
-                ],
-                vec![
-                    None,
-                    None,
-                    None,
-                    None,
-                    Some(38),
-                    Some(10),
-                    None,
-                    Some(10),
-                    None,
-                ],
-                vec![
-                    None,
-                    None,
-                    None,
-                    Some(10),
-                    None,
-                    None,
-                    Some(10),
-                    None,
-                    Some(5),
-                ],
-                vec![None, None, None, None, None, None, None, None, None],
+                vec![(2, 15)],
+                vec![(2, 22), (3, 30), (5, 24)],
+                vec![(0, 15), (1, 22), (4, 22)],
+                vec![(1, 30), (5, 18), (7, 10)],
+                vec![(2, 22), (5, 12), (6, 38)],
+                vec![(1, 24), (3, 18), (4, 12), (6, 10)],
+                vec![(4, 38), (5, 10), (7, 10)],
+                vec![(3, 10), (6, 10), (8, 5)],
+                vec![],
             ],
         }
     }
@@ -648,4 +1033,202 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(154));
     }
+
+    #[test]
+    fn test_last_junction() {
+        assert_eq!(example_trail_graph().last_junction(), Some(7));
+        assert_eq!(
+            example_trail_graph_ignoring_slopes().last_junction(),
+            Some(7)
+        );
+
+        let graph = example_trail_graph_ignoring_slopes();
+        assert_eq!(graph.moves(7, Some(7)), &[(8, 5)]);
+        assert_eq!(graph.moves(6, Some(7)), &[(4, 38), (5, 10), (7, 10)]);
+    }
+
+    /// Tries every hike, without any pruning.
+    fn brute_force(graph: &TrailGraph, position: usize, visited: &mut Vec<bool>) -> Option<u32> {
+        if position == graph.finish {
+            return Some(0);
+        }
+        let mut longest = None;
+        for &(next, steps) in &graph.nodes[position] {
+            if !visited[next] {
+                visited[next] = true;
+                longest = longest.max(brute_force(graph, next, visited).map(|rest| rest + steps));
+                visited[next] = false;
+            }
+        }
+        longest
+    }
+
+    fn random_graph(random: &mut Lcg, size: usize) -> TrailGraph {
+        let mut nodes = vec![Vec::new(); size];
+        for from in 0..size {
+            for to in from + 1..size {
+                if random.below(3) == 0 {
+                    let steps = 1 + u32::try_from(random.below(20)).unwrap();
+                    nodes[from].push((to, steps));
+                    nodes[to].push((from, steps));
+                }
+            }
+        }
+        TrailGraph {
+            start: 0,
+            finish: size - 1,
+            points: (0..size).collect(),
+            nodes,
+        }
+    }
+
+    #[test]
+    fn test_longest_hike_matches_brute_force() {
+        let mut random = Lcg::new(23);
+        for size in 2..=11 {
+            for _ in 0..10 {
+                let graph = random_graph(&mut random, size);
+                let mut visited = vec![false; size];
+                visited[graph.start] = true;
+                let expected = brute_force(&graph, graph.start, &mut visited);
+
+                assert_eq!(
+                    graph.longest_hike().map(|hike| hike.steps),
+                    expected,
+                    "{graph:?}"
+                );
+                for threads in [0, 1, 2, 3, usize::MAX] {
+                    assert_eq!(
+                        graph.longest_hike_parallel(threads).map(|hike| hike.steps),
+                        expected,
+                        "{graph:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_longest_hike_past_64_points() {
+        // a ladder with two rails of 50 points, the finish hanging off the end of the first.
+        let mut nodes = vec![Vec::new(); 101];
+        let mut connect = |from: usize, to: usize| {
+            nodes[from].push((to, 1));
+            nodes[to].push((from, 1));
+        };
+        for rung in 0..50 {
+            connect(rung, 50 + rung);
+            if rung < 49 {
+                connect(rung, rung + 1);
+                connect(50 + rung, 50 + rung + 1);
+            }
+        }
+        connect(49, 100);
+        let graph = TrailGraph {
+            start: 0,
+            finish: 100,
+            points: (0..101).collect(),
+            nodes,
+        };
+
+        // the longest hike zig-zags up the ladder, visiting every point.
+        assert_eq!(graph.last_junction(), Some(49));
+        assert_eq!(graph.longest_hike().map(|hike| hike.steps), Some(100));
+        assert_eq!(
+            graph.longest_hike_parallel(4).map(|hike| hike.steps),
+            Some(100)
+        );
+    }
+
+    #[test]
+    fn test_part_two_parallel() {
+        let trail_map = example_trail_map();
+        assert_eq!(
+            trail_map
+                .graph(false)
+                .longest_hike_parallel(2)
+                .map(|hike| hike.steps),
+            Some(94)
+        );
+        assert_eq!(
+            trail_map
+                .graph(true)
+                .longest_hike_parallel(4)
+                .map(|hike| hike.steps),
+            Some(154)
+        );
+    }
+
+    #[test]
+    fn test_longest_hike_route() {
+        assert_eq!(
+            example_trail_graph().longest_hike(),
+            Some(Hike {
+                steps: 94,
+                route: vec![0, 2, 1, 5, 3, 7, 8]
+            })
+        );
+        assert_eq!(
+            example_trail_graph_ignoring_slopes().longest_hike_parallel(3),
+            Some(Hike {
+                steps: 154,
+                route: vec![0, 2, 4, 6, 5, 1, 3, 7, 8]
+            })
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let graph = example_trail_graph();
+        let hike = graph.longest_hike();
+        let dot = graph.dot(hike.as_ref()).to_string();
+        assert!(dot.starts_with("digraph trails {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("  0 [label=\"start\\n(0, 1)\", shape=box, color=red];\n"));
+        assert!(dot.contains("  4 [label=\"4\\n(13, 5)\", shape=ellipse];\n"));
+        assert!(dot.contains("  2 -> 1 [label=\"22\", color=red, penwidth=2];\n"));
+        assert!(dot.contains("  1 -> 3 [label=\"30\"];\n"));
+
+        // edges which run both ways are only drawn once.
+        let graph = example_trail_graph_ignoring_slopes();
+        let dot = graph.dot(None).to_string();
+        assert!(dot.contains("  1 -> 2 [label=\"22\", dir=none];\n"));
+        assert!(!dot.contains("  2 -> 1 "));
+        assert!(dot.contains("  7 -> 8 [label=\"5\"];\n"));
+        assert_eq!(dot.lines().filter(|line| line.contains("->")).count(), 12);
+    }
+
+    #[test]
+    fn test_corridor() {
+        let trail_map = example_trail_map();
+        let corridor = trail_map.corridor(1, 708, 15, false).unwrap();
+        assert_eq!(corridor.len(), 15);
+        assert_eq!(corridor.first(), Some(&(GRID_SIZE + 1)));
+        assert_eq!(corridor.last(), Some(&708));
+
+        // the slope at (4, 3) can't be climbed, and the steps must match.
+        assert_eq!(trail_map.corridor(708, 1, 15, false), None);
+        assert!(trail_map.corridor(708, 1, 15, true).is_some());
+        assert_eq!(trail_map.corridor(1, 708, 14, false), None);
+    }
+
+    #[test]
+    fn test_hike_frame() {
+        let trail_map = example_trail_map();
+        let graph = trail_map.graph(false);
+        let hike = graph.longest_hike().unwrap();
+        let frame = trail_map.hike_frame(&graph, &hike, false, 23, 23);
+        let plain = frame.plain();
+        let lines: Vec<&str> = plain.lines().collect();
+        assert_eq!(lines.len(), 23);
+        assert_eq!(lines[0], "#S#####################");
+        assert_eq!(lines[1], "#OOOOOOO#########...###");
+        assert_eq!(lines[3], "###OOOOO#OOO>.###.#.###");
+        assert_eq!(lines[22], "#####################O#");
+        assert_eq!(plain.matches('O').count(), 94);
+        assert_eq!(
+            frame.get(3, 11).unwrap().style.background,
+            Some(Colour::BLUE)
+        );
+    }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use advent_of_code::parse::{parse_all, ParseError, Parser};
 
 advent_of_code::solution!(24);
 
@@ -12,53 +12,36 @@ enum Intersection {
     Future { x: i64, y: i64 },
 }
 
-#[derive(Debug, PartialEq)]
-struct ParseHailstoneError;
-
 #[derive(Debug, PartialEq)]
 struct Hailstone2D {
     position: Point2D,
     velocity: Point2D,
 }
 
-impl FromStr for Point2D {
-    type Err = ParseHailstoneError;
-
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let mut x: Result<i64, Self::Err> = Err(ParseHailstoneError);
-        let mut y: Result<i64, Self::Err> = Err(ParseHailstoneError);
-
-        for (pos, part) in text.split(", ").enumerate() {
-            let value = part.trim().parse().map_err(|_| ParseHailstoneError);
-            match pos {
-                0 => x = value,
-                1 => y = value,
-                2 => (),
-                _ => return Err(ParseHailstoneError),
-            }
+impl Point2D {
+    /// Reads the x and y co-ordinates, skipping over the z co-ordinate if present.
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.whitespace();
+        let x = parser.number()?;
+        parser.keyword(",")?;
+        parser.whitespace();
+        let y = parser.number()?;
+        if parser.try_keyword(",") {
+            parser.whitespace();
+            parser.number::<i64>()?;
         }
-
-        let x = x?;
-        let y = y?;
         Ok(Self(x, y))
     }
 }
 
-impl FromStr for Hailstone2D {
-    type Err = ParseHailstoneError;
-
-    fn from_str(text: &str) -> Result<Self, Self::Err> {
-        if let Some((position, velocity)) = text.split_once(" @ ") {
-            let position = position.parse()?;
-            let velocity = velocity.parse()?;
-            Ok(Self { position, velocity })
-        } else {
-            Err(ParseHailstoneError)
-        }
+impl Hailstone2D {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let position = Point2D::parse(parser)?;
+        parser.keyword(" @ ")?;
+        let velocity = Point2D::parse(parser)?;
+        Ok(Self { position, velocity })
     }
-}
 
-impl Hailstone2D {
     const fn intersection(&self, other: &Self) -> Intersection {
         if self.velocity.0 == 0 && self.velocity.1 == 0 {
             return Intersection::None;
@@ -96,15 +79,8 @@ impl Hailstone2D {
     }
 }
 
-fn read_hailstones_2d(input: &str) -> Result<Vec<Hailstone2D>, ParseHailstoneError> {
-    let mut hailstones = Vec::new();
-
-    for line in input.lines() {
-        let hailstone = line.parse()?;
-        hailstones.push(hailstone);
-    }
-
-    Ok(hailstones)
+fn read_hailstones_2d(input: &str) -> Result<Vec<Hailstone2D>, ParseError> {
+    parse_all(input, |parser| parser.lines(Hailstone2D::parse))
 }
 
 #[derive(Debug, PartialEq)]
@@ -0,0 +1,200 @@
+/// Module for finding where a sequence of states starts repeating, so that the state after a huge
+/// number of iterations can be found without running them all. The sequence is the initial state
+/// followed by repeated applications of a `step` function, which must eventually revisit a state
+/// (otherwise the search never finishes).
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The shape of a sequence of states: after the first `prefix` states, the sequence repeats every
+/// `period` states.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The earliest iteration which has the same state as iteration `n` (with the initial state
+    /// being iteration 0).
+    #[must_use]
+    pub const fn equivalent_iteration(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+
+    /// Finds the state at iteration `n` by running only as many steps as it takes to reach an
+    /// equivalent iteration.
+    pub fn state_at<T>(&self, initial: T, step: impl Fn(&T) -> T, n: usize) -> T {
+        (0..self.equivalent_iteration(n)).fold(initial, |state, _| step(&state))
+    }
+}
+
+/// Finds the [`Cycle`] using Brent's algorithm, which keeps only two states in memory at a time.
+pub fn brent<T: Clone + PartialEq>(initial: &T, step: impl Fn(&T) -> T) -> Cycle {
+    // find the period, by moving the tortoise up to the hare at each power of two.
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // find the prefix, by running two states a period apart until they meet.
+    let mut tortoise = initial.clone();
+    let mut hare = (0..period).fold(initial.clone(), |state, _| step(&state));
+    let mut prefix = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Finds the [`Cycle`] using Floyd's algorithm, with a tortoise moving one step at a time and a
+/// hare moving two.
+pub fn floyd<T: Clone + PartialEq>(initial: &T, step: impl Fn(&T) -> T) -> Cycle {
+    let mut tortoise = step(initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&step(&hare));
+    }
+
+    let mut prefix = 0;
+    let mut tortoise = initial.clone();
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// A [`Cycle`] found by remembering every state, which can then give the state at any iteration
+/// without running any more steps.
+#[derive(Debug, PartialEq, Eq)]
+pub struct History<T> {
+    pub cycle: Cycle,
+    states: Vec<T>,
+}
+
+impl<T> History<T> {
+    /// The state at iteration `n`.
+    #[must_use]
+    pub fn state_at(&self, n: usize) -> &T {
+        &self.states[self.cycle.equivalent_iteration(n)]
+    }
+}
+
+/// Finds the [`Cycle`] by hashing each state until one is seen for a second time. This takes the
+/// fewest steps, at the cost of keeping all of the states in memory.
+pub fn hashed<T: Clone + Eq + Hash>(initial: T, step: impl Fn(&T) -> T) -> History<T> {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+
+    loop {
+        if let Some(&prefix) = seen.get(&state) {
+            let cycle = Cycle {
+                prefix,
+                period: states.len() - prefix,
+            };
+            return History { cycle, states };
+        }
+
+        let next = step(&state);
+        seen.insert(state.clone(), states.len());
+        states.push(state);
+        state = next;
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{brent, floyd, hashed, Cycle};
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn step(value: &u32) -> u32 {
+        (value * value + 1) % 255
+    }
+
+    /// Finds the cycle by comparing every pair of states.
+    fn brute_force(initial: u32) -> Cycle {
+        let mut states = vec![initial];
+        loop {
+            let next = step(states.last().unwrap());
+            if let Some(prefix) = states.iter().position(|state| *state == next) {
+                return Cycle {
+                    prefix,
+                    period: states.len() - prefix,
+                };
+            }
+            states.push(next);
+        }
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        for initial in 0..255 {
+            let expected = brute_force(initial);
+            assert_eq!(brent(&initial, step), expected, "brent from {initial}");
+            assert_eq!(floyd(&initial, step), expected, "floyd from {initial}");
+            assert_eq!(
+                hashed(initial, step).cycle,
+                expected,
+                "hashed from {initial}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_known_cycle() {
+        // 3, 10, 101, 2, 5, 26, 167, 95, 101, ...
+        let cycle = brent(&3, step);
+        assert_eq!(
+            cycle,
+            Cycle {
+                prefix: 2,
+                period: 6
+            }
+        );
+        assert_eq!(cycle.equivalent_iteration(1), 1);
+        assert_eq!(cycle.equivalent_iteration(8), 2);
+        assert_eq!(cycle.equivalent_iteration(1_000_000_001), 5);
+    }
+
+    #[test]
+    fn test_state_at() {
+        let history = hashed(3, step);
+        let cycle = history.cycle;
+        for n in 0..100 {
+            let expected = (0..n).fold(3, |state, _| step(&state));
+            assert_eq!(*history.state_at(n), expected);
+            assert_eq!(cycle.state_at(3, step, n), expected);
+        }
+        assert_eq!(*history.state_at(1_000_000_001), 26);
+        assert_eq!(cycle.state_at(3, step, 1_000_000_001), 26);
+    }
+}
@@ -0,0 +1,2 @@
+/// Module for geometry on the integer lattice.
+pub mod polygon;
@@ -0,0 +1,469 @@
+/// Module for lattice polygons: closed shapes whose vertices all have integer coordinates, such
+/// as a loop through a grid or a trench dug by following instructions.
+///
+/// Areas are worked out with the shoelace formula and the points inside with Pick's theorem.
+/// Totals are accumulated as `i128` and checked, so that any overflow is reported as an error
+/// rather than giving a wrong answer.
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::Display;
+
+/// A point on the integer lattice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    #[must_use]
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// The distance between two points moving only horizontally and vertically.
+    #[must_use]
+    pub const fn manhattan_distance(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+/// An error which can be returned when building or measuring a [`Polygon`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    /// The path doesn't finish where it started.
+    Open,
+    /// An edge is neither horizontal nor vertical, so its length isn't a whole number.
+    NotRectilinear,
+    /// A total was too large to hold in an `i128`.
+    Overflow,
+    /// There are fewer than three vertices, or they enclose no area, so there is no inside to
+    /// count points in.
+    Degenerate,
+}
+
+impl Error for PolygonError {}
+
+impl Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "the path doesn't finish where it started"),
+            Self::NotRectilinear => write!(f, "the polygon has a diagonal edge"),
+            Self::Overflow => write!(f, "the polygon is too large to measure"),
+            Self::Degenerate => write!(f, "the polygon encloses no area"),
+        }
+    }
+}
+
+/// Where a point lies relative to a [`Polygon`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Containment {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A polygon given by its vertices in order.
+///
+/// There is an edge from each vertex to the next and from the last back to the first. The edges
+/// may be at any angle, and the polygon may go round either way, but it shouldn't cross itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    /// Creates a [`Polygon`] with the given vertices.
+    #[must_use]
+    pub const fn new(vertices: Vec<Point>) -> Self {
+        Self { vertices }
+    }
+
+    /// Creates a [`Polygon`] from a path which visits each vertex in turn and then returns to
+    /// the first.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Open`] if the path doesn't finish where it started.
+    pub fn from_closed_path(mut path: Vec<Point>) -> Result<Self, PolygonError> {
+        if path.first() != path.last() {
+            return Err(PolygonError::Open);
+        }
+        path.pop();
+        Ok(Self::new(path))
+    }
+
+    #[must_use]
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    /// Each edge of the polygon, as the vertices at its start and end.
+    pub fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(from, to)| (*from, *to))
+    }
+
+    /// Returns `true` if every edge is horizontal or vertical.
+    #[must_use]
+    pub fn is_rectilinear(&self) -> bool {
+        self.edges()
+            .all(|(from, to)| from.x == to.x || from.y == to.y)
+    }
+
+    /// Twice the area of the polygon. Twice the area of a lattice polygon is always a whole
+    /// number, although the area itself may not be.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Overflow`] if the total doesn't fit in an `i128`.
+    pub fn doubled_area(&self) -> Result<i128, PolygonError> {
+        let doubled = self.edges().try_fold(0_i128, |total, (from, to)| {
+            let term =
+                i128::from(from.x) * i128::from(to.y) - i128::from(to.x) * i128::from(from.y);
+            total.checked_add(term).ok_or(PolygonError::Overflow)
+        })?;
+        doubled.checked_abs().ok_or(PolygonError::Overflow)
+    }
+
+    /// The area of the polygon, rounded down. This is exact when the doubled area is even, as it
+    /// always is for rectilinear polygons.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Overflow`] if the area is too large to work out.
+    pub fn area(&self) -> Result<i128, PolygonError> {
+        Ok(self.doubled_area()? / 2)
+    }
+
+    /// The length of the boundary of a rectilinear polygon.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::NotRectilinear`] if any edge is diagonal, or
+    /// [`PolygonError::Overflow`] if the total is too large.
+    pub fn perimeter(&self) -> Result<i128, PolygonError> {
+        if !self.is_rectilinear() {
+            return Err(PolygonError::NotRectilinear);
+        }
+        self.boundary_points()
+    }
+
+    /// The number of lattice points on the boundary of the polygon. Along an edge which moves
+    /// `dx` across and `dy` down there are `gcd(dx, dy)` of them, counting one end.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Overflow`] if the total doesn't fit in an `i128`.
+    pub fn boundary_points(&self) -> Result<i128, PolygonError> {
+        self.edges().try_fold(0_i128, |total, (from, to)| {
+            let points = gcd(from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+            total
+                .checked_add(i128::from(points))
+                .ok_or(PolygonError::Overflow)
+        })
+    }
+
+    /// The number of lattice points strictly inside the polygon. By Pick's theorem, the area is
+    /// the number of points inside plus half the number on the boundary, less one.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Degenerate`] if the polygon encloses no area, or
+    /// [`PolygonError::Overflow`] if the area or boundary is too large to work out.
+    pub fn interior_points(&self) -> Result<i128, PolygonError> {
+        let doubled = self.doubled_area()?;
+        if self.vertices.len() < 3 || doubled == 0 {
+            return Err(PolygonError::Degenerate);
+        }
+        let boundary = self.boundary_points()?;
+        Ok((doubled - boundary) / 2 + 1)
+    }
+
+    /// The number of lattice points inside the polygon or on its boundary.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`PolygonError::Degenerate`] if the polygon encloses no area, or
+    /// [`PolygonError::Overflow`] if the area or boundary is too large to work out.
+    pub fn lattice_points(&self) -> Result<i128, PolygonError> {
+        self.interior_points()?
+            .checked_add(self.boundary_points()?)
+            .ok_or(PolygonError::Overflow)
+    }
+
+    /// Works out whether the point is inside the polygon, on its boundary, or outside, by
+    /// counting the edges crossed by a ray from the point heading towards increasing `x`.
+    #[must_use]
+    pub fn contains(&self, point: Point) -> Containment {
+        let mut inside = false;
+
+        for (from, to) in self.edges() {
+            let (dx, dy) = (
+                i128::from(to.x) - i128::from(from.x),
+                i128::from(to.y) - i128::from(from.y),
+            );
+            let (px, py) = (
+                i128::from(point.x) - i128::from(from.x),
+                i128::from(point.y) - i128::from(from.y),
+            );
+            // which side of the edge the point is on, from the sign of the cross product.
+            let cross = compare_products(dx, py, px, dy);
+
+            let within_x = from.x.min(to.x) <= point.x && point.x <= from.x.max(to.x);
+            let within_y = from.y.min(to.y) <= point.y && point.y <= from.y.max(to.y);
+            if cross == Ordering::Equal && within_x && within_y {
+                return Containment::Boundary;
+            }
+
+            // count edges which straddle the ray, treating each edge as including its lower end
+            // so that the ray passing through a vertex is only counted once.
+            if (from.y > point.y) != (to.y > point.y) && (cross == Ordering::Greater) == (dy > 0) {
+                inside = !inside;
+            }
+        }
+
+        if inside {
+            Containment::Inside
+        } else {
+            Containment::Outside
+        }
+    }
+}
+
+impl FromIterator<Point> for Polygon {
+    fn from_iter<I: IntoIterator<Item = Point>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Compares `a * b` with `c * d` without overflowing. Each factor is a difference between two
+/// `i64`s, so the products can be too large for an `i128`, but their magnitudes always fit in a
+/// `u128`.
+fn compare_products(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    let signed = |x: i128, y: i128| (x.signum() * y.signum(), x.unsigned_abs() * y.unsigned_abs());
+    let (left_sign, left) = signed(a, b);
+    let (right_sign, right) = signed(c, d);
+
+    match left_sign.cmp(&right_sign) {
+        Ordering::Equal if left_sign > 0 => left.cmp(&right),
+        Ordering::Equal if left_sign < 0 => right.cmp(&left),
+        order => order,
+    }
+}
+
+const fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{compare_products, gcd, Containment, Point, Polygon, PolygonError};
+    use std::cmp::Ordering;
+
+    fn polygon(vertices: &[(i64, i64)]) -> Polygon {
+        vertices.iter().map(|(x, y)| Point::new(*x, *y)).collect()
+    }
+
+    /// The lagoon from the 2023 day 18 example, as a path from the origin.
+    fn lagoon() -> Polygon {
+        polygon(&[
+            (0, 0),
+            (6, 0),
+            (6, 5),
+            (4, 5),
+            (4, 7),
+            (6, 7),
+            (6, 9),
+            (1, 9),
+            (1, 7),
+            (0, 7),
+            (0, 5),
+            (2, 5),
+            (2, 2),
+            (0, 2),
+        ])
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(0, 0), 0);
+        assert_eq!(gcd(5, 0), 5);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(gcd(12, 18), 6);
+    }
+
+    #[test]
+    fn test_rectilinear() {
+        let square = polygon(&[(0, 0), (4, 0), (4, 4), (0, 4)]);
+        assert!(square.is_rectilinear());
+        assert_eq!(square.doubled_area(), Ok(32));
+        assert_eq!(square.area(), Ok(16));
+        assert_eq!(square.perimeter(), Ok(16));
+        assert_eq!(square.boundary_points(), Ok(16));
+        assert_eq!(square.interior_points(), Ok(9));
+        assert_eq!(square.lattice_points(), Ok(25));
+
+        let lagoon = lagoon();
+        assert_eq!(lagoon.area(), Ok(42));
+        assert_eq!(lagoon.perimeter(), Ok(38));
+        assert_eq!(lagoon.lattice_points(), Ok(62));
+    }
+
+    #[test]
+    fn test_orientation() {
+        let clockwise = lagoon();
+        let anticlockwise: Polygon = clockwise.vertices().iter().rev().copied().collect();
+        assert_eq!(anticlockwise.doubled_area(), clockwise.doubled_area());
+        assert_eq!(anticlockwise.interior_points(), clockwise.interior_points());
+    }
+
+    #[test]
+    fn test_general() {
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 3)]);
+        assert!(!triangle.is_rectilinear());
+        assert_eq!(triangle.doubled_area(), Ok(12));
+        assert_eq!(triangle.area(), Ok(6));
+        assert_eq!(triangle.perimeter(), Err(PolygonError::NotRectilinear));
+        assert_eq!(triangle.boundary_points(), Ok(4 + 1 + 3));
+        assert_eq!(triangle.interior_points(), Ok(3));
+
+        let half = polygon(&[(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(half.doubled_area(), Ok(1));
+        assert_eq!(half.area(), Ok(0));
+        assert_eq!(half.interior_points(), Ok(0));
+        assert_eq!(half.lattice_points(), Ok(3));
+
+        let diamond = polygon(&[(2, 0), (4, 2), (2, 4), (0, 2)]);
+        assert_eq!(diamond.area(), Ok(8));
+        assert_eq!(diamond.boundary_points(), Ok(8));
+        assert_eq!(diamond.interior_points(), Ok(5));
+    }
+
+    #[test]
+    fn test_from_closed_path() {
+        let path = vec![
+            Point::new(0, 0),
+            Point::new(2, 0),
+            Point::new(2, 2),
+            Point::new(0, 0),
+        ];
+        assert_eq!(
+            Polygon::from_closed_path(path),
+            Ok(polygon(&[(0, 0), (2, 0), (2, 2)]))
+        );
+        let path = vec![Point::new(0, 0), Point::new(2, 0), Point::new(2, 2)];
+        assert_eq!(Polygon::from_closed_path(path), Err(PolygonError::Open));
+        assert_eq!(
+            Polygon::from_closed_path(Vec::new()),
+            Ok(Polygon::default())
+        );
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert_eq!(
+            Polygon::default().lattice_points(),
+            Err(PolygonError::Degenerate)
+        );
+        let point = polygon(&[(3, 3)]);
+        assert_eq!(point.interior_points(), Err(PolygonError::Degenerate));
+        let line = polygon(&[(0, 0), (4, 0)]);
+        assert_eq!(line.area(), Ok(0));
+        assert_eq!(line.interior_points(), Err(PolygonError::Degenerate));
+        let flat = polygon(&[(0, 0), (2, 2), (4, 4)]);
+        assert_eq!(flat.lattice_points(), Err(PolygonError::Degenerate));
+    }
+
+    #[test]
+    fn test_contains() {
+        let lagoon = lagoon();
+        for (point, expected) in [
+            ((3, 3), Containment::Inside),
+            ((1, 1), Containment::Inside),
+            ((5, 8), Containment::Inside),
+            ((0, 0), Containment::Boundary),
+            ((3, 0), Containment::Boundary),
+            ((2, 4), Containment::Boundary),
+            ((4, 6), Containment::Boundary),
+            ((1, 3), Containment::Outside),
+            ((5, 6), Containment::Outside),
+            ((7, 5), Containment::Outside),
+            ((-1, 2), Containment::Outside),
+            ((0, 10), Containment::Outside),
+        ] {
+            assert_eq!(
+                lagoon.contains(Point::new(point.0, point.1)),
+                expected,
+                "{point:?}"
+            );
+        }
+
+        // every point is counted exactly once.
+        let inside = (-1..=7)
+            .flat_map(|x| (-1..=10).map(move |y| Point::new(x, y)))
+            .filter(|point| lagoon.contains(*point) != Containment::Outside)
+            .count();
+        assert_eq!(inside, 62);
+
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 3)]);
+        assert_eq!(triangle.contains(Point::new(1, 1)), Containment::Inside);
+        assert_eq!(triangle.contains(Point::new(4, 0)), Containment::Boundary);
+        assert_eq!(triangle.contains(Point::new(3, 2)), Containment::Outside);
+        assert_eq!(triangle.contains(Point::new(-1, 0)), Containment::Outside);
+    }
+
+    #[test]
+    fn test_compare_products() {
+        assert_eq!(compare_products(2, 3, 1, 6), Ordering::Equal);
+        assert_eq!(compare_products(2, 3, -1, 6), Ordering::Greater);
+        assert_eq!(compare_products(-2, 3, -1, 5), Ordering::Less);
+        assert_eq!(compare_products(0, 3, -1, 5), Ordering::Greater);
+        assert_eq!(compare_products(0, 3, 5, 0), Ordering::Equal);
+
+        let big = i128::from(u64::MAX);
+        assert_eq!(compare_products(big, big, big, big - 1), Ordering::Greater);
+        assert_eq!(compare_products(-big, big, big, -big), Ordering::Equal);
+        assert_eq!(compare_products(-big, big, -big, big - 1), Ordering::Less);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let huge = polygon(&[
+            (i64::MIN, i64::MIN),
+            (i64::MAX, i64::MIN),
+            (i64::MAX, i64::MAX),
+            (i64::MIN, i64::MAX),
+        ]);
+        assert_eq!(huge.doubled_area(), Err(PolygonError::Overflow));
+        assert_eq!(huge.interior_points(), Err(PolygonError::Overflow));
+        assert_eq!(huge.perimeter(), Ok(4 * (i128::from(u64::MAX))));
+        for (point, expected) in [
+            ((i64::MAX - 1, i64::MAX - 1), Containment::Inside),
+            ((0, 0), Containment::Inside),
+            ((i64::MAX, 0), Containment::Boundary),
+            ((i64::MIN, i64::MIN), Containment::Boundary),
+        ] {
+            assert_eq!(
+                huge.contains(Point::new(point.0, point.1)),
+                expected,
+                "{point:?}"
+            );
+        }
+
+        let large = polygon(&[(0, 0), (i64::MAX, 0), (i64::MAX, i64::MAX), (0, i64::MAX)]);
+        let side = i128::from(i64::MAX);
+        assert_eq!(large.area(), Ok(side * side));
+    }
+}
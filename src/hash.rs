@@ -0,0 +1,67 @@
+/// Module for the Holiday ASCII String Helper algorithm (HASH) from 2023 day 15.
+///
+/// HASH turns any string into a value from 0 to 255: starting from zero, each character's code is
+/// added, the total is multiplied by 17, and the result is kept to the remainder after dividing
+/// by 256. It is simple enough to work out by hand, and spreads short labels across 256 boxes.
+#[must_use]
+pub fn hash(value: &str) -> usize {
+    value
+        .chars()
+        .fold(0, |acc, ch| (acc + (ch as usize)) * 17 % 256)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::hash;
+
+    const DOCUMENTED_HASHES: [(&str, usize); 12] = [
+        ("HASH", 52),
+        ("rn=1", 30),
+        ("cm-", 253),
+        ("qp=3", 97),
+        ("cm=2", 47),
+        ("qp-", 14),
+        ("pc=4", 180),
+        ("ot=9", 9),
+        ("ab=5", 197),
+        ("pc-", 48),
+        ("pc=6", 214),
+        ("ot=7", 231),
+    ];
+
+    #[test]
+    fn test_hash() {
+        for (step, expected) in DOCUMENTED_HASHES {
+            assert_eq!(hash(step), expected, "hash of {step}");
+        }
+    }
+
+    #[test]
+    fn test_hash_properties() {
+        assert_eq!(hash(""), 0);
+        for (step, _) in DOCUMENTED_HASHES {
+            // each character is folded into the hash of everything before it.
+            for (ix, ch) in step.char_indices() {
+                assert_eq!(
+                    hash(&step[..=ix]),
+                    (hash(&step[..ix]) + ch as usize) * 17 % 256
+                );
+            }
+        }
+        // the running values from the puzzle's worked example of hashing "HASH".
+        assert_eq!(hash("H"), 200);
+        assert_eq!(hash("HA"), 153);
+        assert_eq!(hash("HAS"), 172);
+        assert_eq!(hash("HASH"), 52);
+
+        // the labels from the example land in the boxes the puzzle description puts them in.
+        assert_eq!(hash("rn"), 0);
+        assert_eq!(hash("cm"), 0);
+        assert_eq!(hash("qp"), 1);
+        assert_eq!(hash("pc"), 3);
+        assert_eq!(hash("ot"), 3);
+        assert_eq!(hash("ab"), 3);
+    }
+}
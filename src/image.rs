@@ -0,0 +1,353 @@
+/// Module for saving rendered frames as image files, for grids which are too big to read in the
+/// terminal. Images can be written as binary PPM or as PNG (using a minimal encoder which stores
+/// the pixel data uncompressed), and a sequence of frames can be written as numbered files to be
+/// assembled into an animation.
+use crate::render::{Cell, Colour, Frame};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+}
+
+impl Format {
+    /// Picks the format from a file extension, if it is one of the supported ones.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            Self::Png => "png",
+        }
+    }
+}
+
+/// A grid of pixels, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Colour>,
+}
+
+impl Image {
+    /// Draws each cell of the frame as a `scale` by `scale` square in the cell's colour.
+    #[must_use]
+    pub fn from_frame(frame: &Frame, scale: usize) -> Self {
+        let width = frame.width() * scale;
+        let height = frame.height() * scale;
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y / scale, x / scale)))
+            .map(|(row, col)| frame.get(row, col).map_or(Colour::BLACK, Cell::colour))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<Colour> {
+        if x < self.width {
+            self.pixels.get(y * self.width + x).copied()
+        } else {
+            None
+        }
+    }
+
+    fn rgb(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pixels
+            .iter()
+            .flat_map(|colour| [colour.red, colour.green, colour.blue])
+    }
+
+    /// The image as a binary (P6) PPM file.
+    #[must_use]
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.rgb());
+        out
+    }
+
+    /// The image as a PNG file, with 8-bit RGB pixels and no compression.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the image is more than `u32::MAX` pixels wide or high.
+    #[must_use]
+    pub fn to_png(&self) -> Vec<u8> {
+        let width = u32::try_from(self.width).expect("image width fits in a PNG");
+        let height = u32::try_from(self.height).expect("image height fits in a PNG");
+
+        let mut header = Vec::with_capacity(13);
+        header.extend(width.to_be_bytes());
+        header.extend(height.to_be_bytes());
+        // bit depth 8, colour type 2 (RGB), default compression, filtering and no interlacing.
+        header.extend([8, 2, 0, 0, 0]);
+
+        // each scanline starts with its filter type, which is always 0 (none).
+        let mut scanlines = Vec::with_capacity(self.height * (1 + self.width * 3));
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            scanlines.push(0);
+            scanlines.extend(
+                row.iter()
+                    .flat_map(|colour| [colour.red, colour.green, colour.blue]),
+            );
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_chunk(&mut out, *b"IHDR", &header);
+        write_chunk(&mut out, *b"IDAT", &zlib_stored(&scanlines));
+        write_chunk(&mut out, *b"IEND", &[]);
+        out
+    }
+
+    /// The image encoded in the given format.
+    #[must_use]
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Ppm => self.to_ppm(),
+            Format::Png => self.to_png(),
+        }
+    }
+
+    /// Saves the image, in the format given by the file's extension.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error if the extension isn't `.ppm` or `.png`, or the file can't be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = Format::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "image files must end in .ppm or .png",
+            )
+        })?;
+        fs::write(path, self.encode(format))
+    }
+}
+
+/// Saves each frame as a numbered image (`0000.png`, `0001.png` and so on) in `directory`,
+/// creating it if necessary, and returns the number of images written.
+///
+/// # Errors
+///
+/// Will return an error if the directory can't be created or any of the images can't be written.
+pub fn save_sequence(
+    directory: impl AsRef<Path>,
+    frames: impl IntoIterator<Item = Frame>,
+    scale: usize,
+    format: Format,
+) -> io::Result<usize> {
+    let directory = directory.as_ref();
+    fs::create_dir_all(directory)?;
+
+    let mut count = 0;
+    for frame in frames {
+        let path = directory.join(format!("{count:04}.{}", format.extension()));
+        fs::write(path, Image::from_frame(&frame, scale).encode(format))?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// How many pixels wide and high each cell is drawn by [`export`] and [`export_sequence`].
+pub const SCALE: usize = 4;
+
+/// Saves the frame to `path` for a solution's `--export` option, reporting how it went.
+pub fn export(frame: &Frame, path: &str) {
+    match Image::from_frame(frame, SCALE).save(path) {
+        Ok(()) => println!("Saved {path}"),
+        Err(err) => eprintln!("Failed to save {path}: {err}"),
+    }
+}
+
+/// Saves the frames as numbered PNGs in `directory` for a solution's `--frames` option,
+/// reporting how it went.
+pub fn export_sequence(frames: impl IntoIterator<Item = Frame>, directory: &str) {
+    match save_sequence(directory, frames, SCALE, Format::Png) {
+        Ok(count) => println!("Saved {count} frames in {directory}"),
+        Err(err) => eprintln!("Failed to save frames in {directory}: {err}"),
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+fn write_chunk(out: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) {
+    let length = u32::try_from(data.len()).expect("PNG chunk fits in 4GB");
+    out.extend(length.to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut n: u32 = 0;
+    while n < 256 {
+        let mut crc = n;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[n as usize] = crc;
+        n += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(u32::MAX, |crc, byte| {
+        CRC_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    let (a, b) = data.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + u32::from(*byte)) % MODULUS;
+        (a, (b + a) % MODULUS)
+    });
+    (b << 16) | a
+}
+
+/// Wraps the data in a zlib stream made up of uncompressed ("stored") deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 0xffff;
+
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(BLOCK_SIZE).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let length = u16::try_from(block.len()).expect("blocks are at most 0xffff bytes");
+        out.push(u8::from(blocks.peek().is_none()));
+        out.extend(length.to_le_bytes());
+        out.extend((!length).to_le_bytes());
+        out.extend(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{adler32, crc32, zlib_stored, Format, Image};
+    use crate::render::{Cell, Colour, Frame, Style};
+    use std::path::Path;
+
+    fn example_image() -> Image {
+        let frame = Frame::from_fn(2, 1, |_, col| {
+            if col == 0 {
+                Cell::new('#', Style::fg(Colour::RED))
+            } else {
+                Cell::new(' ', Style::PLAIN.on(Colour::new(1, 2, 3)))
+            }
+        });
+        Image::from_frame(&frame, 2)
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"IEND"), 0xae42_6082);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    }
+
+    #[test]
+    fn test_from_frame() {
+        let image = example_image();
+        assert_eq!((image.width(), image.height()), (4, 2));
+        assert_eq!(image.get(1, 1), Some(Colour::RED));
+        assert_eq!(image.get(2, 0), Some(Colour::new(1, 2, 3)));
+        assert_eq!(image.get(4, 0), None);
+        assert_eq!(image.get(0, 2), None);
+    }
+
+    #[test]
+    fn test_ppm() {
+        let ppm = example_image().to_ppm();
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(ppm.len(), header.len() + 4 * 2 * 3);
+        assert_eq!(
+            &ppm[header.len()..header.len() + 9],
+            &[220, 50, 47, 220, 50, 47, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_png() {
+        let png = example_image().to_png();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[8..16], b"\0\0\0\x0dIHDR");
+        assert_eq!(&png[16..29], &[0, 0, 0, 4, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+        // the IDAT chunk holds two scanlines of one filter byte and four pixels each.
+        let idat_length = u32::from_be_bytes([png[33], png[34], png[35], png[36]]);
+        assert_eq!(&png[37..41], b"IDAT");
+        assert_eq!(idat_length, 2 + 5 + 2 * 13 + 4);
+    }
+
+    #[test]
+    fn test_zlib_stored() {
+        assert_eq!(
+            zlib_stored(b"abc"),
+            vec![0x78, 0x01, 1, 3, 0, 0xfc, 0xff, b'a', b'b', b'c', 0x02, 0x4d, 0x01, 0x27]
+        );
+
+        let data = vec![7; 0x1_0000];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 5 + 0xffff + 5 + 1 + 4);
+        assert_eq!(stream[2], 0);
+        assert_eq!(stream[2 + 5 + 0xffff], 1);
+        assert_eq!(zlib_stored(b"").len(), 2 + 5 + 4);
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::from_path(Path::new("out.png")), Some(Format::Png));
+        assert_eq!(
+            Format::from_path(Path::new("dir/out.PPM")),
+            Some(Format::Ppm)
+        );
+        assert_eq!(Format::from_path(Path::new("out.gif")), None);
+        assert_eq!(Format::from_path(Path::new("out")), None);
+    }
+}
@@ -0,0 +1,576 @@
+/// Module for working with half-open intervals of integers: sets of intervals, and piecewise
+/// translations (such as the almanac maps of day 5) which can be composed into one another.
+use std::ops::{Add, Sub};
+
+/// Values which can be used as the bounds of an [`Interval`].
+pub trait Bound: Copy + Default + Ord + Add<Output = Self> + Sub<Output = Self> {}
+
+impl<T: Copy + Default + Ord + Add<Output = T> + Sub<Output = T>> Bound for T {}
+
+/// A half-open range of values, from `start` (inclusive) to `end` (exclusive).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Interval<T> {
+    /// Creates the [`Interval`] `start..end`.
+    #[must_use]
+    pub const fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+}
+
+impl<T: Bound> Interval<T> {
+    /// Returns `true` if the interval contains no values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// The number of values in the interval.
+    #[must_use]
+    pub fn length(&self) -> T {
+        if self.is_empty() {
+            T::default()
+        } else {
+            self.end - self.start
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    /// Returns the values common to both intervals, or [`None`] if there are none.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let overlap = Self::new(self.start.max(other.start), self.end.min(other.end));
+        (!overlap.is_empty()).then_some(overlap)
+    }
+
+    /// Splits the interval into the values below `point` and the values at or above it.
+    #[must_use]
+    pub fn split_at(&self, point: T) -> (Option<Self>, Option<Self>) {
+        let below = Self::new(self.start, point.min(self.end));
+        let above = Self::new(point.max(self.start), self.end);
+        (
+            (!below.is_empty()).then_some(below),
+            (!above.is_empty()).then_some(above),
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A set of values, stored as sorted and disjoint [`Interval`]s.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Bound> IntervalSet<T> {
+    /// Creates an empty [`IntervalSet`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// The disjoint intervals making up the set, in ascending order.
+    #[must_use]
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Interval<T>> {
+        self.intervals.iter()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    #[must_use]
+    pub fn contains(&self, value: T) -> bool {
+        let ix = self
+            .intervals
+            .partition_point(|interval| interval.end <= value);
+        self.intervals
+            .get(ix)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    /// The smallest value in the set.
+    #[must_use]
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|interval| interval.start)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        self.intervals.push(interval);
+        self.normalise();
+    }
+
+    /// Sorts the intervals, dropping empty ones and merging any which overlap or touch.
+    fn normalise(&mut self) {
+        self.intervals.retain(|interval| !interval.is_empty());
+        self.intervals.sort_unstable();
+
+        let mut merged: Vec<Interval<T>> = Vec::with_capacity(self.intervals.len());
+        for interval in self.intervals.drain(..) {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+        self.intervals = merged;
+    }
+
+    /// Returns the values which are in either set.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    /// Returns the values which are in both sets.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut left, mut right) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.intervals.get(left), other.intervals.get(right)) {
+            intervals.extend(a.intersection(b));
+            if a.end <= b.end {
+                left += 1;
+            } else {
+                right += 1;
+            }
+        }
+
+        Self { intervals }
+    }
+
+    /// Returns the values which are in this set but not in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+
+        for interval in &self.intervals {
+            let mut cursor = interval.start;
+            let first = other
+                .intervals
+                .partition_point(|cut| cut.end <= interval.start);
+
+            for cut in other.intervals[first..]
+                .iter()
+                .take_while(|cut| cut.start < interval.end)
+            {
+                if cut.start > cursor {
+                    intervals.push(Interval::new(cursor, cut.start));
+                }
+                cursor = cursor.max(cut.end);
+            }
+
+            if cursor < interval.end {
+                intervals.push(Interval::new(cursor, interval.end));
+            }
+        }
+
+        Self { intervals }
+    }
+}
+
+impl<T: Bound> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        std::iter::once(interval).collect()
+    }
+}
+
+impl<T: Bound> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = Self {
+            intervals: iter.into_iter().collect(),
+        };
+        set.normalise();
+        set
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Part of a [`PiecewiseMap`]: the values in `source` are shifted so that `source.start` maps
+/// to `destination`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment<T> {
+    pub source: Interval<T>,
+    pub destination: T,
+}
+
+impl<T: Bound> Segment<T> {
+    #[must_use]
+    pub fn map(&self, value: T) -> T {
+        self.destination + (value - self.source.start)
+    }
+
+    /// The interval of values which `source` is mapped onto.
+    #[must_use]
+    pub fn image(&self) -> Interval<T> {
+        Interval::new(self.destination, self.destination + self.source.length())
+    }
+}
+
+/// A function which shifts each of a number of disjoint intervals by its own offset, leaving all
+/// other values unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PiecewiseMap<T> {
+    segments: Vec<Segment<T>>,
+}
+
+impl<T: Bound> PiecewiseMap<T> {
+    /// Creates a [`PiecewiseMap`] which maps every value to itself.
+    #[must_use]
+    pub const fn identity() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+
+    /// Composes the maps into a single one, applying them in the order given.
+    pub fn compose<'a>(maps: impl IntoIterator<Item = &'a Self>) -> Self
+    where
+        T: 'a,
+    {
+        maps.into_iter()
+            .fold(Self::identity(), |composed, map| composed.then(map))
+    }
+
+    /// The non-identity segments of the map, sorted by their source intervals.
+    #[must_use]
+    pub fn segments(&self) -> &[Segment<T>] {
+        &self.segments
+    }
+
+    /// The values at which the map's offset changes, in ascending order. Between two neighbouring
+    /// breakpoints the map is increasing, so its minimum over any interval is found at the start
+    /// of the interval or at one of the breakpoints inside it.
+    #[must_use]
+    pub fn breakpoints(&self) -> Vec<T> {
+        let mut points: Vec<T> = self
+            .segments
+            .iter()
+            .flat_map(|segment| [segment.source.start, segment.source.end])
+            .collect();
+        points.dedup();
+        points
+    }
+
+    /// Returns the map which undoes this one, or [`None`] if this map sends more than one value
+    /// to the same place.
+    #[must_use]
+    pub fn inverse(&self) -> Option<Self> {
+        let sources: IntervalSet<T> = self.segments.iter().map(|segment| segment.source).collect();
+        let images: IntervalSet<T> = self.segments.iter().map(Segment::image).collect();
+
+        // the images must not overlap, and must exactly fill the gaps left in the identity.
+        let mapped = self.segments.iter().fold(T::default(), |total, segment| {
+            total + segment.source.length()
+        });
+        let covered = images
+            .iter()
+            .fold(T::default(), |total, interval| total + interval.length());
+        let images_overlap = mapped != covered;
+        if images_overlap || images != sources {
+            return None;
+        }
+
+        let mut inverse = Self {
+            segments: self
+                .segments
+                .iter()
+                .map(|segment| Segment {
+                    source: segment.image(),
+                    destination: segment.source.start,
+                })
+                .collect(),
+        };
+        inverse.normalise();
+        Some(inverse)
+    }
+
+    /// Maps the values in `source` so that `source.start` maps to `destination`. Any of those
+    /// values which are already mapped by an earlier segment keep their existing mapping.
+    pub fn insert(&mut self, source: Interval<T>, destination: T) {
+        let covered: IntervalSet<T> = self.segments.iter().map(|segment| segment.source).collect();
+        let uncovered = IntervalSet::from(source).difference(&covered);
+
+        self.segments.extend(uncovered.iter().map(|piece| Segment {
+            source: *piece,
+            destination: destination + (piece.start - source.start),
+        }));
+        self.normalise();
+    }
+
+    /// Sorts the segments, dropping any which map values to themselves and merging neighbours
+    /// which continue the same shift.
+    fn normalise(&mut self) {
+        self.segments.retain(|segment| {
+            !segment.source.is_empty() && segment.destination != segment.source.start
+        });
+        self.segments
+            .sort_unstable_by_key(|segment| segment.source.start);
+
+        let mut merged: Vec<Segment<T>> = Vec::with_capacity(self.segments.len());
+        for segment in self.segments.drain(..) {
+            match merged.last_mut() {
+                Some(last)
+                    if last.source.end == segment.source.start
+                        && last.image().end == segment.destination =>
+                {
+                    last.source.end = segment.source.end;
+                }
+                _ => merged.push(segment),
+            }
+        }
+        self.segments = merged;
+    }
+
+    #[must_use]
+    pub fn map(&self, value: T) -> T {
+        let ix = self
+            .segments
+            .partition_point(|segment| segment.source.end <= value);
+        match self.segments.get(ix) {
+            Some(segment) if segment.source.contains(value) => segment.map(value),
+            _ => value,
+        }
+    }
+
+    /// Cuts `interval` at the boundaries of the map's segments, returning each piece along with
+    /// the value its start is mapped to. Pieces outside of any segment map to themselves.
+    #[must_use]
+    pub fn split(&self, interval: Interval<T>) -> Vec<Segment<T>> {
+        let mut pieces = Vec::new();
+        let mut cursor = interval.start;
+        let first = self
+            .segments
+            .partition_point(|segment| segment.source.end <= interval.start);
+
+        for segment in self.segments[first..]
+            .iter()
+            .take_while(|segment| segment.source.start < interval.end)
+        {
+            if segment.source.start > cursor {
+                pieces.push(Segment {
+                    source: Interval::new(cursor, segment.source.start),
+                    destination: cursor,
+                });
+                cursor = segment.source.start;
+            }
+
+            let end = segment.source.end.min(interval.end);
+            pieces.push(Segment {
+                source: Interval::new(cursor, end),
+                destination: segment.map(cursor),
+            });
+            cursor = end;
+        }
+
+        if cursor < interval.end {
+            pieces.push(Segment {
+                source: Interval::new(cursor, interval.end),
+                destination: cursor,
+            });
+        }
+
+        pieces
+    }
+
+    /// Maps every value in `interval`, returning the (possibly overlapping) resulting intervals.
+    #[must_use]
+    pub fn map_interval(&self, interval: Interval<T>) -> Vec<Interval<T>> {
+        self.split(interval).iter().map(Segment::image).collect()
+    }
+
+    /// Maps every value in `set`.
+    #[must_use]
+    pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        set.iter()
+            .flat_map(|interval| self.map_interval(*interval))
+            .collect()
+    }
+
+    /// Returns the map which applies this map followed by `next`.
+    #[must_use]
+    pub fn then(&self, next: &Self) -> Self {
+        let mut segments = Vec::new();
+
+        // values shifted by this map are then cut up and shifted again by the next map.
+        for segment in &self.segments {
+            for piece in next.split(segment.image()) {
+                let offset = piece.source.start - segment.destination;
+                segments.push(Segment {
+                    source: Interval::new(
+                        segment.source.start + offset,
+                        segment.source.start + offset + piece.source.length(),
+                    ),
+                    destination: piece.destination,
+                });
+            }
+        }
+
+        // values left alone by this map are only affected by the next map.
+        let covered: IntervalSet<T> = self.segments.iter().map(|segment| segment.source).collect();
+        for segment in &next.segments {
+            let uncovered = IntervalSet::from(segment.source).difference(&covered);
+            segments.extend(uncovered.iter().map(|piece| Segment {
+                source: *piece,
+                destination: segment.map(piece.start),
+            }));
+        }
+
+        let mut composed = Self { segments };
+        composed.normalise();
+        composed
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Interval, IntervalSet, PiecewiseMap, Segment};
+
+    fn set(intervals: &[(u32, u32)]) -> IntervalSet<u32> {
+        intervals
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn test_interval_split_at() {
+        let interval = Interval::new(10, 20);
+        assert_eq!(
+            interval.split_at(15),
+            (Some(Interval::new(10, 15)), Some(Interval::new(15, 20)))
+        );
+        assert_eq!(interval.split_at(5), (None, Some(interval)));
+        assert_eq!(interval.split_at(20), (Some(interval), None));
+        assert_eq!(interval.length(), 10);
+        assert_eq!(Interval::new(5, 3).length(), 0);
+    }
+
+    #[test]
+    fn test_set_normalisation() {
+        assert_eq!(
+            set(&[(5, 8), (1, 3), (3, 4), (7, 10), (12, 12)]).intervals(),
+            &[Interval::new(1, 4), Interval::new(5, 10)]
+        );
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(1, 5), (10, 15)]);
+        let b = set(&[(3, 12), (14, 20)]);
+
+        assert_eq!(a.union(&b), set(&[(1, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 12), (14, 15)]));
+        assert_eq!(a.difference(&b), set(&[(1, 3), (12, 14)]));
+        assert_eq!(b.difference(&a), set(&[(5, 10), (15, 20)]));
+        assert!(a.contains(14));
+        assert!(!a.contains(5));
+        assert_eq!(a.min(), Some(1));
+    }
+
+    fn seed_to_soil() -> PiecewiseMap<u64> {
+        let mut map = PiecewiseMap::identity();
+        map.insert(Interval::new(98, 100), 50);
+        map.insert(Interval::new(50, 98), 52);
+        map
+    }
+
+    #[test]
+    fn test_map() {
+        let map = seed_to_soil();
+        assert_eq!(map.map(79), 81);
+        assert_eq!(map.map(14), 14);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(
+            map.map_interval(Interval::new(45, 100)),
+            vec![
+                Interval::new(45, 50),
+                Interval::new(52, 100),
+                Interval::new(50, 52)
+            ]
+        );
+        assert_eq!(
+            map.map_set(&IntervalSet::from(Interval::new(45, 100))),
+            IntervalSet::from(Interval::new(45, 100))
+        );
+    }
+
+    #[test]
+    fn test_insert_keeps_earlier_segments() {
+        let mut map = PiecewiseMap::identity();
+        map.insert(Interval::new(10, 20), 110);
+        map.insert(Interval::new(15, 30), 215);
+        assert_eq!(
+            map.segments(),
+            &[
+                Segment {
+                    source: Interval::new(10, 20),
+                    destination: 110
+                },
+                Segment {
+                    source: Interval::new(20, 30),
+                    destination: 220
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compose() {
+        let first = seed_to_soil();
+        let mut second = PiecewiseMap::identity();
+        second.insert(Interval::new(15, 52), 0);
+        second.insert(Interval::new(52, 54), 37);
+        second.insert(Interval::new(0, 15), 39);
+
+        let composed = PiecewiseMap::compose([&first, &second]);
+        for value in 0..120 {
+            assert_eq!(composed.map(value), second.map(first.map(value)));
+        }
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        assert_eq!(seed_to_soil().breakpoints(), vec![50, 98, 100]);
+        assert_eq!(PiecewiseMap::<u64>::identity().breakpoints(), vec![]);
+    }
+
+    #[test]
+    fn test_inverse() {
+        let map = seed_to_soil();
+        let inverse = map.inverse().expect("seed-to-soil map is a bijection");
+        for value in 0..120 {
+            assert_eq!(inverse.map(map.map(value)), value);
+        }
+
+        let mut squashed = PiecewiseMap::identity();
+        squashed.insert(Interval::new(10, 20), 30);
+        squashed.insert(Interval::new(30, 40), 35);
+        assert_eq!(squashed.inverse(), None);
+
+        let mut overlapping = PiecewiseMap::identity();
+        overlapping.insert(Interval::new(10, 20), 30);
+        overlapping.insert(Interval::new(30, 40), 10);
+        overlapping.insert(Interval::new(40, 45), 32);
+        assert_eq!(overlapping.inverse(), None);
+    }
+}
@@ -1,4 +1,12 @@
+pub mod cycle;
 mod day;
+pub mod geometry;
+pub mod hash;
+pub mod image;
+pub mod intervals;
+pub mod parse;
+pub mod random;
+pub mod render;
 pub mod template;
 
 pub use day::*;
@@ -1,4 +1,4 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve};
+use advent_of_code::template::commands::{all, download, read, scaffold, show, solve};
 use args::{parse, AppArguments};
 
 mod args {
@@ -15,12 +15,21 @@ mod args {
         },
         Scaffold {
             day: Day,
+            template: Option<String>,
+            force: bool,
+            dry_run: bool,
+        },
+        Show {
+            day: Day,
+            write_example: bool,
+            block: usize,
         },
         Solve {
             day: Day,
             release: bool,
             time: bool,
             submit: Option<u8>,
+            options: Vec<String>,
         },
         All {
             release: bool,
@@ -31,7 +40,7 @@ mod args {
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
         let mut args = pico_args::Arguments::from_env();
 
-        let app_args = match args.subcommand()?.as_deref() {
+        let mut app_args = match args.subcommand()?.as_deref() {
             Some("all") => AppArguments::All {
                 release: args.contains("--release"),
                 time: args.contains("--time"),
@@ -44,12 +53,21 @@ mod args {
             },
             Some("scaffold") => AppArguments::Scaffold {
                 day: args.free_from_str()?,
+                template: args.opt_value_from_str("--template")?,
+                force: args.contains("--force"),
+                dry_run: args.contains("--dry-run"),
+            },
+            Some("show") => AppArguments::Show {
+                day: args.free_from_str()?,
+                write_example: args.contains("--write-example"),
+                block: args.opt_value_from_str("--block")?.unwrap_or(1),
             },
             Some("solve") => AppArguments::Solve {
                 day: args.free_from_str()?,
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
                 time: args.contains("--time"),
+                options: Vec::new(),
             },
             Some(x) => {
                 eprintln!("Unknown command: {x}");
@@ -62,7 +80,14 @@ mod args {
         };
 
         let remaining = args.finish();
-        if !remaining.is_empty() {
+        if let AppArguments::Solve { options, .. } = &mut app_args {
+            // anything left over is passed through to the solution, for days with options of their own.
+            options.extend(
+                remaining
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().into_owned()),
+            );
+        } else if !remaining.is_empty() {
             eprintln!("Warning: unknown argument(s): {remaining:?}.");
         }
 
@@ -80,13 +105,24 @@ fn main() {
             AppArguments::All { release, time } => all::handle(release, time),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
-            AppArguments::Scaffold { day } => scaffold::handle(day),
+            AppArguments::Scaffold {
+                day,
+                template,
+                force,
+                dry_run,
+            } => scaffold::handle(day, template.as_deref(), force, dry_run),
+            AppArguments::Show {
+                day,
+                write_example,
+                block,
+            } => show::handle(day, write_example, block),
             AppArguments::Solve {
                 day,
                 release,
                 time,
                 submit,
-            } => solve::handle(day, release, time, submit),
+                options,
+            } => solve::handle(day, release, time, submit, &options),
         },
     };
 }
@@ -0,0 +1,442 @@
+/// Small parser combinator toolkit for reading puzzle inputs.
+///
+/// A [`Parser`] is a cursor over the puzzle text. Each combinator either consumes the text it
+/// recognises and returns a value, or returns a [`ParseError`] which records the line and column
+/// at which parsing failed.
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// An error which can be returned by any of the [`Parser`] combinators.
+///
+/// # Display
+/// This value displays as the expected input followed by its position.
+///
+/// ```
+/// # use advent_of_code::parse::Parser;
+/// let err = Parser::new("Game x").keyword("Game 1").unwrap_err();
+/// assert_eq!(err.to_string(), "expected `Game 1` at line 1, column 1")
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line at which parsing failed, counting from 1.
+    pub line: usize,
+    /// The column (in characters) at which parsing failed, counting from 1.
+    pub column: usize,
+    /// A description of the input which was expected at this position.
+    pub expected: String,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} at line {}, column {}",
+            self.expected, self.line, self.column
+        )
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// A cursor over some puzzle text.
+///
+/// Sub-parsers created by [`Parser::line`] and [`Parser::block`] share the same source text, so
+/// errors raised inside them still report positions relative to the whole input.
+#[derive(Clone, Debug)]
+pub struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+    limit: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a [`Parser`] positioned at the start of `source`.
+    #[must_use]
+    pub const fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            limit: source.len(),
+        }
+    }
+
+    /// Returns the text which has not been consumed yet.
+    #[must_use]
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.offset..self.limit]
+    }
+
+    /// Returns `true` if all of the text has been consumed.
+    #[must_use]
+    pub const fn is_finished(&self) -> bool {
+        self.offset >= self.limit
+    }
+
+    /// Builds a [`ParseError`] describing what was expected at the current position.
+    #[must_use]
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        self.error_at(self.offset, expected)
+    }
+
+    fn error_at(&self, offset: usize, expected: impl Into<String>) -> ParseError {
+        let before = &self.source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |ix| ix + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            expected: expected.into(),
+        }
+    }
+
+    /// Consumes `keyword` if the remaining text starts with it, returning whether it did so.
+    pub fn try_keyword(&mut self, keyword: &str) -> bool {
+        if self.rest().starts_with(keyword) {
+            self.offset += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes exactly the text `keyword`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if the remaining text does not start with `keyword`.
+    pub fn keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        if self.try_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("`{}`", keyword.escape_debug())))
+        }
+    }
+
+    /// Consumes the first of the given keywords which matches, returning its associated value.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if none of the keywords match the remaining text.
+    pub fn one_of<T: Clone>(&mut self, options: &[(&str, T)]) -> Result<T, ParseError> {
+        for (keyword, value) in options {
+            if self.try_keyword(keyword) {
+                return Ok(value.clone());
+            }
+        }
+
+        let names: Vec<String> = options
+            .iter()
+            .map(|(keyword, _)| format!("`{}`", keyword.escape_debug()))
+            .collect();
+        Err(self.error(format!("one of {}", names.join(", "))))
+    }
+
+    /// Consumes the longest run of characters matching `predicate`, which may be empty.
+    pub fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let length = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.offset += length;
+        &rest[..length]
+    }
+
+    /// Consumes a non-empty run of alphanumeric characters.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if the remaining text does not start with an alphanumeric
+    /// character.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        let word = self.take_while(char::is_alphanumeric);
+        if word.is_empty() {
+            Err(self.error("word"))
+        } else {
+            Ok(word)
+        }
+    }
+
+    /// Skips any spaces or tabs (but not newlines).
+    pub fn whitespace(&mut self) {
+        self.take_while(|c| c == ' ' || c == '\t');
+    }
+
+    /// Consumes an optionally negative decimal number and converts it to `T`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if the remaining text does not start with a number, or if the
+    /// number does not fit into `T`.
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.offset;
+        self.try_keyword("-");
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            self.offset = start;
+            return Err(self.error("number"));
+        }
+
+        self.source[start..self.offset]
+            .parse()
+            .map_err(|_| self.error_at(start, "number in range"))
+    }
+
+    /// Parses one or more `item`s, separated by `separator`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if any of the items fail to parse.
+    pub fn separated_by<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.try_keyword(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Parses one or more `item`s on the current line, separated by spaces or tabs.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if any of the items fail to parse.
+    pub fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        loop {
+            let start = self.offset;
+            self.whitespace();
+            if self.offset == start || self.is_finished() || self.rest().starts_with('\n') {
+                return Ok(items);
+            }
+            items.push(item(self)?);
+        }
+    }
+
+    /// Parses `item` from the text up to the next newline, which must be fully consumed. The
+    /// newline itself is left in place.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if `item` fails, or if it leaves part of the line unconsumed.
+    pub fn line<T>(
+        &mut self,
+        item: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let end = self
+            .rest()
+            .find('\n')
+            .map_or(self.limit, |ix| self.offset + ix);
+        self.bounded(end, item)
+    }
+
+    /// Parses one or more lines with `item`, tolerating a single trailing newline.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if any of the lines fail to parse.
+    pub fn lines<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![self.line(&mut item)?];
+        while self.try_keyword("\n") && !self.is_finished() {
+            items.push(self.line(&mut item)?);
+        }
+        Ok(items)
+    }
+
+    /// Parses `item` from the text up to the next blank line, which must be fully consumed (apart
+    /// from a trailing newline). The blank line itself is left in place.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if `item` fails, or if it leaves part of the block unconsumed.
+    pub fn block<T>(
+        &mut self,
+        item: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let end = self
+            .rest()
+            .find("\n\n")
+            .map_or(self.limit, |ix| self.offset + ix);
+        self.bounded(end, |parser| {
+            let value = item(parser)?;
+            parser.try_keyword("\n");
+            Ok(value)
+        })
+    }
+
+    /// Parses one or more blocks with `item`, separated by blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if any of the blocks fail to parse.
+    pub fn blocks<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        self.separated_by("\n\n", |parser| parser.block(&mut item))
+    }
+
+    /// Checks that all of the text has been consumed, allowing for trailing whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Will return `ParseError` if any non-whitespace text remains.
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.take_while(char::is_whitespace);
+        if self.is_finished() {
+            Ok(())
+        } else {
+            Err(self.error("end of input"))
+        }
+    }
+
+    fn bounded<T>(
+        &mut self,
+        end: usize,
+        item: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let mut inner = Self {
+            source: self.source,
+            offset: self.offset,
+            limit: end,
+        };
+        let value = item(&mut inner)?;
+        if !inner.is_finished() {
+            return Err(inner.error("end of line"));
+        }
+        self.offset = end;
+        Ok(value)
+    }
+}
+
+/// Parses the whole of `source` with `item`, requiring that all of it is consumed (apart from
+/// trailing whitespace).
+///
+/// # Errors
+///
+/// Will return `ParseError` if `item` fails, or if it leaves part of `source` unconsumed.
+pub fn parse_all<'a, T>(
+    source: &'a str,
+    item: impl FnOnce(&mut Parser<'a>) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let mut parser = Parser::new(source);
+    let value = item(&mut parser)?;
+    parser.end()?;
+    Ok(value)
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{parse_all, ParseError, Parser};
+
+    fn error(line: usize, column: usize, expected: &str) -> ParseError {
+        ParseError {
+            line,
+            column,
+            expected: expected.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(Parser::new("42abc").number::<u32>(), Ok(42));
+        assert_eq!(Parser::new("-17").number::<i64>(), Ok(-17));
+        assert_eq!(
+            Parser::new("abc").number::<u32>(),
+            Err(error(1, 1, "number"))
+        );
+        assert_eq!(Parser::new("-").number::<i32>(), Err(error(1, 1, "number")));
+        assert_eq!(
+            Parser::new("300").number::<u8>(),
+            Err(error(1, 1, "number in range"))
+        );
+    }
+
+    #[test]
+    fn test_keyword_and_one_of() {
+        let mut parser = Parser::new("Game 12: red");
+        assert_eq!(parser.keyword("Game "), Ok(()));
+        assert_eq!(parser.number::<u32>(), Ok(12));
+        assert_eq!(parser.keyword(": "), Ok(()));
+        assert_eq!(
+            parser.clone().one_of(&[("green", 1), ("blue", 2)]),
+            Err(error(1, 10, "one of `green`, `blue`"))
+        );
+        assert_eq!(parser.one_of(&[("green", 1), ("red", 0)]), Ok(0));
+        assert!(parser.is_finished());
+    }
+
+    #[test]
+    fn test_separated_by_and_list() {
+        assert_eq!(
+            parse_all("1, 2, 3", |p| p.separated_by(", ", Parser::number::<u32>)),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(
+            parse_all("1  2\t3 ", |p| p.list(Parser::number::<u32>)),
+            Ok(vec![1, 2, 3])
+        );
+        assert_eq!(
+            parse_all("1, 2, x", |p| p.separated_by(", ", Parser::number::<u32>)),
+            Err(error(1, 7, "number"))
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let parse_line = |p: &mut Parser| -> Result<(u32, u32), ParseError> {
+            let a = p.number()?;
+            p.keyword(",")?;
+            let b = p.number()?;
+            Ok((a, b))
+        };
+
+        assert_eq!(
+            parse_all("1,2\n3,4\n", |p| p.lines(parse_line)),
+            Ok(vec![(1, 2), (3, 4)])
+        );
+        assert_eq!(
+            parse_all("1,2\n3,4x\n5,6", |p| p.lines(parse_line)),
+            Err(error(2, 4, "end of line"))
+        );
+        assert_eq!(
+            parse_all("1,2\n3;4", |p| p.lines(parse_line)),
+            Err(error(2, 2, "`,`"))
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let parse_block = |p: &mut Parser| -> Result<Vec<u32>, ParseError> {
+            p.keyword("block:\n")?;
+            p.lines(Parser::number)
+        };
+
+        assert_eq!(
+            parse_all("block:\n1\n2\n\nblock:\n3\n", |p| p.blocks(parse_block)),
+            Ok(vec![vec![1, 2], vec![3]])
+        );
+        assert_eq!(
+            parse_all("block:\n1\n\nblock:\n3\nx", |p| p.blocks(parse_block)),
+            Err(error(6, 1, "number"))
+        );
+    }
+
+    #[test]
+    fn test_end() {
+        assert_eq!(parse_all("12\n\n", Parser::number::<u32>), Ok(12));
+        assert_eq!(
+            parse_all("12 13", Parser::number::<u32>),
+            Err(error(1, 4, "end of input"))
+        );
+    }
+}
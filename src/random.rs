@@ -0,0 +1,67 @@
+/// Module for a small, seeded pseudo-random number generator.
+///
+/// It builds repeatable test cases, such as random puzzle inputs to check a fast solver against
+/// a simple one: the same seed always gives the same numbers. It is a 64-bit linear congruential
+/// generator using Knuth's MMIX constants, which is plenty for test data but no good for anything
+/// which needs real randomness.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+    const INCREMENT: u64 = 1;
+
+    #[must_use]
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// The next number from 0 up to (but not including) `limit`, taken from the high bits of the
+    /// state, which are the most random.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `limit` is zero.
+    pub fn below(&mut self, limit: usize) -> usize {
+        self.state = self
+            .state
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+        let high = usize::try_from(self.state >> 33).unwrap_or(usize::MAX);
+        high % limit
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Lcg;
+
+    #[test]
+    fn test_repeatable() {
+        let numbers = |seed| {
+            let mut random = Lcg::new(seed);
+            (0..20).map(|_| random.below(1000)).collect::<Vec<usize>>()
+        };
+        assert_eq!(numbers(7), numbers(7));
+        assert_ne!(numbers(7), numbers(8));
+    }
+
+    #[test]
+    fn test_below() {
+        let mut random = Lcg::new(1);
+        let mut seen = [0; 6];
+        for _ in 0..6000 {
+            seen[random.below(6)] += 1;
+        }
+        // every value turns up, and none far more often than the others.
+        assert!(
+            seen.iter().all(|count| (800..1200).contains(count)),
+            "{seen:?}"
+        );
+        assert_eq!(Lcg::new(0).below(1), 0);
+    }
+}
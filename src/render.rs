@@ -0,0 +1,338 @@
+/// Module for drawing puzzle grids as frames of coloured characters, which can be printed to the
+/// terminal (using 24-bit ANSI colour codes) or played back as an animation.
+use std::fmt;
+use std::io::{stdout, Write};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Colour {
+    pub const BLACK: Self = Self::new(0, 0, 0);
+    pub const WHITE: Self = Self::new(255, 255, 255);
+    pub const GREY: Self = Self::new(110, 110, 110);
+    pub const RED: Self = Self::new(220, 50, 47);
+    pub const ORANGE: Self = Self::new(240, 140, 30);
+    pub const YELLOW: Self = Self::new(250, 220, 60);
+    pub const GREEN: Self = Self::new(90, 190, 70);
+    pub const CYAN: Self = Self::new(60, 190, 200);
+    pub const BLUE: Self = Self::new(50, 100, 210);
+    pub const PURPLE: Self = Self::new(140, 80, 190);
+
+    #[must_use]
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// Mixes this colour with `other`, in the proportion `amount` out of `total` (so an `amount`
+    /// of zero gives this colour, and an `amount` of `total` gives `other`).
+    #[must_use]
+    pub fn blend(self, other: Self, amount: usize, total: usize) -> Self {
+        let amount = amount.min(total);
+        let mix = |from: u8, to: u8| {
+            let mixed =
+                (usize::from(from) * (total - amount) + usize::from(to) * amount) / total.max(1);
+            u8::try_from(mixed).unwrap_or(u8::MAX)
+        };
+        Self::new(
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+        )
+    }
+
+    /// Picks the colour for `value` on a scale from zero to `max`, blending evenly between the
+    /// given colour `stops`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `stops` is empty.
+    #[must_use]
+    pub fn gradient(stops: &[Self], value: usize, max: usize) -> Self {
+        let segments = stops.len() - 1;
+        if segments == 0 || max == 0 {
+            return stops[0];
+        }
+
+        let scaled = value.min(max) * segments;
+        let segment = (scaled / max).min(segments - 1);
+        let amount = scaled - (segment * max);
+        stops[segment].blend(stops[segment + 1], amount, max)
+    }
+}
+
+/// How a single character is drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub foreground: Option<Colour>,
+    pub background: Option<Colour>,
+    pub bold: bool,
+}
+
+impl Style {
+    pub const PLAIN: Self = Self {
+        foreground: None,
+        background: None,
+        bold: false,
+    };
+
+    #[must_use]
+    pub const fn fg(colour: Colour) -> Self {
+        Self {
+            foreground: Some(colour),
+            ..Self::PLAIN
+        }
+    }
+
+    #[must_use]
+    pub const fn on(self, colour: Colour) -> Self {
+        Self {
+            background: Some(colour),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    fn write_codes(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[0")?;
+        if self.bold {
+            f.write_str(";1")?;
+        }
+        if let Some(Colour { red, green, blue }) = self.foreground {
+            write!(f, ";38;2;{red};{green};{blue}")?;
+        }
+        if let Some(Colour { red, green, blue }) = self.background {
+            write!(f, ";48;2;{red};{green};{blue}")?;
+        }
+        f.write_str("m")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub style: Style,
+}
+
+impl Cell {
+    #[must_use]
+    pub const fn new(symbol: char, style: Style) -> Self {
+        Self { symbol, style }
+    }
+
+    #[must_use]
+    pub const fn plain(symbol: char) -> Self {
+        Self::new(symbol, Style::PLAIN)
+    }
+
+    /// The single colour which best stands for the cell when it is drawn as a block of pixels:
+    /// its background if it has one, otherwise its foreground (unless the symbol is blank).
+    #[must_use]
+    pub const fn colour(&self) -> Colour {
+        match (self.style.background, self.style.foreground) {
+            (Some(background), _) => background,
+            (None, Some(foreground)) if !self.symbol.is_whitespace() => foreground,
+            (None, None) if !self.symbol.is_whitespace() => Colour::WHITE,
+            _ => Colour::BLACK,
+        }
+    }
+}
+
+/// A rectangular grid of styled characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    /// Creates a frame by calling `cell` with the row and column of each position in turn.
+    pub fn from_fn(width: usize, height: usize, cell: impl Fn(usize, usize) -> Cell) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(|(row, col)| cell(row, col))
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn get(&self, row: usize, col: usize) -> Option<&Cell> {
+        if col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the cell at the given position, if it is inside the frame.
+    pub fn set(&mut self, row: usize, col: usize, cell: Cell) {
+        if row < self.height && col < self.width {
+            self.cells[row * self.width + col] = cell;
+        }
+    }
+
+    /// The rows of the frame, without any styling.
+    #[must_use]
+    pub fn plain(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1))
+            .map(|row| row.iter().map(|cell| cell.symbol).collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+/// Draws the frame with ANSI escape codes, only changing style where neighbouring cells differ
+/// and resetting it at the end of each row.
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            let mut current = Style::PLAIN;
+            for cell in row {
+                if cell.style != current {
+                    cell.style.write_codes(f)?;
+                    current = cell.style;
+                }
+                write!(f, "{}", cell.symbol)?;
+            }
+            if current != Style::PLAIN {
+                f.write_str("\x1b[0m")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The number of rows and columns in a puzzle input laid out as a grid.
+#[must_use]
+pub fn dimensions(input: &str) -> (usize, usize) {
+    let rows = input.lines().count();
+    let cols = input.lines().map(str::len).max().unwrap_or_default();
+    (rows, cols)
+}
+
+/// Prints each frame in turn, drawing over the previous one, with `delay` between them.
+pub fn animate(frames: impl IntoIterator<Item = Frame>, delay: Duration) {
+    let mut stdout = stdout();
+    let mut previous_height = None;
+
+    print!("\x1b[?25l");
+    for frame in frames {
+        if let Some(height) = previous_height {
+            thread::sleep(delay);
+            print!("\x1b[{height}A");
+        }
+        print!("{frame}");
+        let _ = stdout.flush();
+        previous_height = Some(frame.height());
+    }
+    println!("\x1b[?25h");
+}
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{dimensions, Cell, Colour, Frame, Style};
+
+    #[test]
+    fn test_blend() {
+        let from = Colour::new(0, 100, 200);
+        let to = Colour::new(100, 200, 0);
+        assert_eq!(from.blend(to, 0, 4), from);
+        assert_eq!(from.blend(to, 4, 4), to);
+        assert_eq!(from.blend(to, 1, 4), Colour::new(25, 125, 150));
+        assert_eq!(from.blend(to, 9, 4), to);
+    }
+
+    #[test]
+    fn test_gradient() {
+        let stops = [Colour::BLACK, Colour::new(100, 0, 0), Colour::WHITE];
+        assert_eq!(Colour::gradient(&stops, 0, 10), Colour::BLACK);
+        assert_eq!(Colour::gradient(&stops, 5, 10), Colour::new(100, 0, 0));
+        assert_eq!(Colour::gradient(&stops, 10, 10), Colour::WHITE);
+        assert_eq!(Colour::gradient(&stops, 20, 10), Colour::WHITE);
+        assert_eq!(Colour::gradient(&stops, 1, 4), Colour::new(50, 0, 0));
+        assert_eq!(Colour::gradient(&[Colour::RED], 3, 10), Colour::RED);
+    }
+
+    fn example_frame() -> Frame {
+        Frame::from_fn(3, 2, |row, col| {
+            if row == col {
+                Cell::new('#', Style::fg(Colour::RED).bold())
+            } else {
+                Cell::plain('.')
+            }
+        })
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut frame = example_frame();
+        assert_eq!(frame.plain(), "#..\n.#.\n");
+        assert_eq!(
+            frame.get(1, 1),
+            Some(&Cell::new('#', Style::fg(Colour::RED).bold()))
+        );
+        assert_eq!(frame.get(0, 3), None);
+
+        frame.set(0, 2, Cell::plain('x'));
+        frame.set(5, 5, Cell::plain('y'));
+        assert_eq!(frame.plain(), "#.x\n.#.\n");
+    }
+
+    #[test]
+    fn test_frame_ansi() {
+        let red = "\x1b[0;1;38;2;220;50;47m";
+        let reset = "\x1b[0m";
+        assert_eq!(
+            example_frame().to_string(),
+            format!("{red}#{reset}..\n.{red}#{reset}.\n")
+        );
+
+        let frame = Frame::from_fn(2, 1, |_, _| {
+            Cell::new(' ', Style::PLAIN.on(Colour::new(1, 2, 3)))
+        });
+        assert_eq!(frame.to_string(), "\x1b[0;48;2;1;2;3m  \x1b[0m\n");
+    }
+
+    #[test]
+    fn test_cell_colour() {
+        let red = Style::fg(Colour::RED);
+        assert_eq!(Cell::new('#', red).colour(), Colour::RED);
+        assert_eq!(Cell::new(' ', red).colour(), Colour::BLACK);
+        assert_eq!(Cell::new(' ', red.on(Colour::BLUE)).colour(), Colour::BLUE);
+        assert_eq!(Cell::plain('x').colour(), Colour::WHITE);
+        assert_eq!(Cell::plain(' ').colour(), Colour::BLACK);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert_eq!(dimensions("..#\n#..\n...\n.#.\n"), (4, 3));
+        assert_eq!(dimensions(""), (0, 0));
+    }
+}
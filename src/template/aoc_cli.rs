@@ -106,11 +106,13 @@ fn get_input_path(day: Day) -> String {
     format!("data/inputs/{day}.txt")
 }
 
-fn get_puzzle_path(day: Day) -> String {
+#[must_use]
+pub fn get_puzzle_path(day: Day) -> String {
     format!("data/puzzles/{day}.md")
 }
 
-fn get_year() -> Option<u16> {
+#[must_use]
+pub fn get_year() -> Option<u16> {
     std::env::var("AOC_YEAR").map_or(None, |x| x.parse().ok().or(None))
 }
 
@@ -2,4 +2,5 @@ pub mod all;
 pub mod download;
 pub mod read;
 pub mod scaffold;
+pub mod show;
 pub mod solve;
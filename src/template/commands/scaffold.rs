@@ -1,11 +1,21 @@
 use std::{
-    fs::{File, OpenOptions},
-    io::Write,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::Path,
     process,
 };
 
+use crate::template::{aoc_cli, puzzle};
 use crate::Day;
 
+/// Directory holding additional module templates, selected with `--template <name>`.
+///
+/// Templates may contain the placeholders `DAY_NUMBER`, `YEAR_NUMBER`, `PUZZLE_TITLE`,
+/// `PART_ONE_ANSWER`, `PART_TWO_ANSWER` and `PART_TWO_EXAMPLE` (an expression reading the example
+/// which the part two answer belongs to).
+const TEMPLATES_DIR: &str = "templates";
+const DEFAULT_TEMPLATE: &str = "default";
+
 const MODULE_TEMPLATE: &str = r#"advent_of_code::solution!(DAY_NUMBER);
 
 #[must_use]
@@ -25,13 +35,13 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        assert_eq!(result, PART_ONE_ANSWER);
     }
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        let result = part_two(&PART_TWO_EXAMPLE);
+        assert_eq!(result, PART_TWO_ANSWER);
     }
 }
 "#;
@@ -48,53 +58,271 @@ fn create_file(path: &str) -> Result<File, std::io::Error> {
         .open(path)
 }
 
-pub fn handle(day: Day) {
+/// Renders an expected example answer as the `Option` literal asserted by the generated tests.
+fn answer_literal(answer: Option<&String>) -> String {
+    match answer.map(|answer| answer.parse::<u64>()) {
+        Some(Ok(value)) => {
+            // group digits in threes to keep `clippy::unreadable_literal` happy.
+            let digits = value.to_string();
+            let mut literal = String::new();
+            for (ix, digit) in digits.chars().enumerate() {
+                if ix > 0 && digits.len() > 5 && (digits.len() - ix) % 3 == 0 {
+                    literal.push('_');
+                }
+                literal.push(digit);
+            }
+            format!("Some({literal})")
+        }
+        _ => "None".into(),
+    }
+}
+
+fn template_names() -> Vec<String> {
+    let mut names = vec![DEFAULT_TEMPLATE.to_string()];
+    if let Ok(entries) = fs::read_dir(TEMPLATES_DIR) {
+        let mut custom: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_suffix(".rs").map(String::from)
+            })
+            .collect();
+        custom.sort();
+        names.extend(custom);
+    }
+    names
+}
+
+/// Loads a template by name. Only the names listed by [`template_names`] are accepted, so that a
+/// name can't reach a file outside the templates directory.
+fn load_template(name: &str) -> Result<String, io::Error> {
+    if name == DEFAULT_TEMPLATE {
+        Ok(MODULE_TEMPLATE.to_string())
+    } else if template_names().iter().any(|template| template == name) {
+        fs::read_to_string(format!("{TEMPLATES_DIR}/{name}.rs"))
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no such template in \"{TEMPLATES_DIR}\""),
+        ))
+    }
+}
+
+fn module_contents(
+    template: &str,
+    day: Day,
+    title: &str,
+    answers: &[Option<String>; 2],
+    separate_part_two_example: bool,
+) -> String {
+    let fits_u32 = answers
+        .iter()
+        .flatten()
+        .all(|answer| answer.parse::<u32>().is_ok() || answer.parse::<u64>().is_err());
+
+    // Without a year, leave it out of the header rather than leaving a gap where it would go.
+    let template = aoc_cli::get_year().map_or_else(
+        || {
+            template
+                .replace(" YEAR_NUMBER", "")
+                .replace("YEAR_NUMBER", "")
+        },
+        |year| template.replace("YEAR_NUMBER", &year.to_string()),
+    );
+
+    let contents = template
+        .replace("DAY_NUMBER", &day.into_inner().to_string())
+        .replace("PUZZLE_TITLE", title)
+        .replace("PART_ONE_ANSWER", &answer_literal(answers[0].as_ref()))
+        .replace("PART_TWO_ANSWER", &answer_literal(answers[1].as_ref()))
+        .replace(
+            "PART_TWO_EXAMPLE",
+            if separate_part_two_example {
+                r#"advent_of_code::template::read_file_part("examples", DAY, 2)"#
+            } else {
+                r#"advent_of_code::template::read_file("examples", DAY)"#
+            },
+        );
+
+    if fits_u32 {
+        contents
+    } else {
+        contents.replace("Option<u32>", "Option<u64>")
+    }
+}
+
+/// A file which scaffolding is about to write, along with what it held beforehand (if it already
+/// existed) so that the write can be rolled back.
+struct PlannedFile {
+    path: String,
+    description: String,
+    contents: String,
+    previous: Option<Vec<u8>>,
+}
+
+enum Step {
+    Write(PlannedFile),
+    Keep(String),
+}
+
+/// Plans writing a data file, unless it already has something in it. With `force`, a file is
+/// overwritten only if there are new contents to replace it with: scaffolding never has any for
+/// the input, so a downloaded input is always kept.
+fn plan_data_file(path: String, kind: &str, contents: String, force: bool) -> Step {
+    let has_contents = |previous: &[u8]| !previous.iter().all(u8::is_ascii_whitespace);
+    match fs::read(&path) {
+        Ok(previous) if has_contents(&previous) && contents.is_empty() => Step::Keep(format!(
+            "Keeping existing {kind} \"{path}\" (nothing to replace it with)"
+        )),
+        Ok(previous) if has_contents(&previous) && !force => Step::Keep(format!(
+            "Keeping existing {kind} \"{path}\" (use --force to overwrite it)"
+        )),
+        previous => Step::Write(PlannedFile {
+            description: if contents.is_empty() {
+                format!("empty {kind} \"{path}\"")
+            } else {
+                format!("{kind} \"{path}\" from puzzle description")
+            },
+            path,
+            contents,
+            previous: previous.ok(),
+        }),
+    }
+}
+
+/// Undoes the given writes, most recent first: files that did not exist before are removed
+/// and files that did are restored to their previous contents.
+fn rollback(written: &[&PlannedFile]) {
+    for file in written.iter().rev() {
+        let result = file.previous.as_ref().map_or_else(
+            || fs::remove_file(&file.path),
+            |previous| fs::write(&file.path, previous),
+        );
+        match result {
+            Ok(()) => println!("Rolled back {}", file.description),
+            Err(e) => eprintln!("Failed to roll back {}: {e}", file.description),
+        }
+    }
+}
+
+fn execute(steps: &[Step]) -> Result<(), io::Error> {
+    let mut written = Vec::new();
+
+    for step in steps {
+        let file = match step {
+            Step::Keep(message) => {
+                println!("{message}");
+                continue;
+            }
+            Step::Write(file) => file,
+        };
+
+        let opened = if file.previous.is_some() {
+            create_file(&file.path)
+        } else {
+            safe_create_file(&file.path)
+        };
+
+        let result = opened.and_then(|mut handle| {
+            written.push(file);
+            handle.write_all(file.contents.as_bytes())
+        });
+
+        if let Err(e) = result {
+            eprintln!("Failed to write {}: {e}", file.description);
+            rollback(&written);
+            return Err(e);
+        }
+
+        if file.previous.is_some() {
+            println!("Overwrote {}", file.description);
+        } else {
+            println!("Created {}", file.description);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle(day: Day, template: Option<&str>, force: bool, dry_run: bool) {
     let input_path = format!("data/inputs/{day}.txt");
     let example_path = format!("data/examples/{day}.txt");
+    let part_two_example_path = format!("data/examples/{day}-2.txt");
     let module_path = format!("src/bin/{day}.rs");
 
-    let mut file = match safe_create_file(&module_path) {
-        Ok(file) => file,
+    if Path::new(&module_path).exists() {
+        eprintln!("Module file \"{module_path}\" already exists, not scaffolding over it.");
+        process::exit(1);
+    }
+
+    // if the puzzle has already been downloaded, pull the example and its answers out of it.
+    let blocks = puzzle::read(day)
+        .map(|markdown| puzzle::parse_blocks(&markdown))
+        .unwrap_or_default();
+    let example = puzzle::code_blocks(&blocks).first().copied();
+    // part two often reuses the first example, but when it gives its own, test against that.
+    let part_two_example = puzzle::part_two_example(&blocks);
+    let answers = [
+        puzzle::example_answer(&blocks, 1),
+        puzzle::example_answer(&blocks, 2),
+    ];
+    let title = puzzle::title(&blocks).unwrap_or_else(|| format!("Day {day}"));
+
+    let template_name = template.unwrap_or(DEFAULT_TEMPLATE);
+    let template = match load_template(template_name) {
+        Ok(template) => template,
         Err(e) => {
-            eprintln!("Failed to create module file: {e}");
+            eprintln!("Failed to load template \"{template_name}\": {e}");
+            eprintln!("Available templates: {}", template_names().join(", "));
             process::exit(1);
         }
     };
 
-    match file.write_all(
-        MODULE_TEMPLATE
-            .replace("DAY_NUMBER", &day.into_inner().to_string())
-            .as_bytes(),
-    ) {
-        Ok(()) => {
-            println!("Created module file \"{}\"", &module_path);
-        }
-        Err(e) => {
-            eprintln!("Failed to write module contents: {e}");
-            process::exit(1);
-        }
+    let mut steps = vec![
+        Step::Write(PlannedFile {
+            description: format!("module file \"{module_path}\""),
+            path: module_path,
+            contents: module_contents(&template, day, &title, &answers, part_two_example.is_some()),
+            previous: None,
+        }),
+        plan_data_file(input_path, "input file", String::new(), force),
+        plan_data_file(
+            example_path,
+            "example file",
+            example.unwrap_or_default().to_string(),
+            force,
+        ),
+    ];
+    if let Some(part_two_example) = part_two_example {
+        steps.push(plan_data_file(
+            part_two_example_path,
+            "part two example file",
+            part_two_example.to_string(),
+            force,
+        ));
     }
 
-    match create_file(&input_path) {
-        Ok(_) => {
-            println!("Created empty input file \"{}\"", &input_path);
-        }
-        Err(e) => {
-            eprintln!("Failed to create input file: {e}");
-            process::exit(1);
+    if dry_run {
+        for step in &steps {
+            match step {
+                Step::Keep(message) => println!("{message}"),
+                Step::Write(file) if file.previous.is_some() => {
+                    println!("Would overwrite {}", file.description);
+                }
+                Step::Write(file) => println!("Would create {}", file.description),
+            }
         }
+    } else if execute(&steps).is_err() {
+        process::exit(1);
     }
 
-    match create_file(&example_path) {
-        Ok(_) => {
-            println!("Created empty example file \"{}\"", &example_path);
-        }
-        Err(e) => {
-            eprintln!("Failed to create example file: {e}");
-            process::exit(1);
+    for (part, answer) in answers.iter().enumerate() {
+        if let Some(answer) = answer {
+            println!("Found example answer {answer} for part {}", part + 1);
         }
     }
 
-    println!("---");
-    println!("🎄 Type `cargo solve {day}` to run your solution.");
+    if !dry_run {
+        println!("---");
+        println!("🎄 Type `cargo solve {day}` to run your solution.");
+    }
 }
@@ -0,0 +1,47 @@
+use std::process;
+
+use crate::template::puzzle::{self, WRAP_WIDTH};
+use crate::Day;
+
+pub fn handle(day: Day, write_example: bool, block: usize) {
+    let markdown = match puzzle::read(day) {
+        Ok(markdown) => markdown,
+        Err(e) => {
+            eprintln!("Failed to read puzzle file: {e}");
+            eprintln!("Try running `cargo download {day}` first.");
+            process::exit(1);
+        }
+    };
+
+    let blocks = puzzle::parse_blocks(&markdown);
+    println!("{}", puzzle::render(&blocks, WRAP_WIDTH));
+
+    if !write_example {
+        return;
+    }
+
+    let Some(example) = block
+        .checked_sub(1)
+        .and_then(|ix| puzzle::code_blocks(&blocks).get(ix).copied())
+    else {
+        eprintln!("Puzzle description has no code block {block}.");
+        process::exit(1);
+    };
+
+    match puzzle::write_example(day, example) {
+        Ok(true) => {
+            println!("---");
+            println!("🎄 Wrote block {block} to example file \"data/examples/{day}.txt\".");
+        }
+        Ok(false) => {
+            println!("---");
+            println!(
+                "🎄 Example file \"data/examples/{day}.txt\" is not empty, leaving it as it is."
+            );
+        }
+        Err(e) => {
+            eprintln!("Failed to write example file: {e}");
+            process::exit(1);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use crate::Day;
 ///
 /// Will panic if the Cargo command spawned by this function fails to start or
 /// panics itself during execution.
-pub fn handle(day: Day, release: bool, time: bool, submit_part: Option<u8>) {
+pub fn handle(day: Day, release: bool, time: bool, submit_part: Option<u8>, options: &[String]) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
     if release {
@@ -24,6 +24,8 @@ pub fn handle(day: Day, release: bool, time: bool, submit_part: Option<u8>) {
         cmd_args.push("--time".to_string());
     }
 
+    cmd_args.extend(options.iter().cloned());
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
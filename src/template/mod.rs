@@ -3,6 +3,7 @@ use std::{env, fs};
 
 pub mod aoc_cli;
 pub mod commands;
+pub mod puzzle;
 pub mod readme_benchmarks;
 pub mod runner;
 
@@ -23,10 +24,33 @@ pub fn read_file(folder: &str, day: Day) -> String {
     f.expect("could not open input file")
 }
 
+/// Helper function that reads the example for one part of a puzzle, for puzzles which give that
+/// part an example of its own, e.g. `data/examples/01-2.txt`.
+///
+/// # Panics
+///
+/// Will panic if the user has insufficient permissions to access the current directory.
+#[must_use]
+pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
+    let cwd = env::current_dir().unwrap();
+    let filepath = cwd
+        .join("data")
+        .join(folder)
+        .join(format!("{day}-{part}.txt"));
+    let f = fs::read_to_string(filepath);
+    f.expect("could not open input file")
+}
+
 /// Creates the constant `DAY` and sets up the input and runner for each part.
+///
+/// An optional second argument names a function `fn(&str)` which is called with the input after
+/// both parts have run, for days which offer extra output behind their own command-line flags.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
+        $crate::solution!($day, |_| ());
+    };
+    ($day:expr, $extras:expr) => {
         /// The current day.
         const DAY: advent_of_code::Day = advent_of_code::day!($day);
 
@@ -35,6 +59,8 @@ macro_rules! solution {
             let input = advent_of_code::template::read_file("inputs", DAY);
             run_part(part_one, &input, DAY, 1);
             run_part(part_two, &input, DAY, 2);
+            let extras: fn(&str) = $extras;
+            extras(&input);
         }
     };
 }
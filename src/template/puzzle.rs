@@ -0,0 +1,525 @@
+/// Module that reads the puzzle descriptions written by aoc-cli and renders them in the terminal.
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use crate::template::{aoc_cli, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use crate::Day;
+
+/// The column at which rendered paragraphs are wrapped.
+pub const WRAP_WIDTH: usize = 80;
+
+/// A single block-level element of a puzzle description.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Block {
+    Heading(String),
+    Paragraph(String),
+    ListItem(String),
+    Code(String),
+}
+
+/// Reads the puzzle description for `day` from `data/puzzles`.
+///
+/// # Errors
+///
+/// Will return `io::Error` if the puzzle file has not been downloaded yet.
+pub fn read(day: Day) -> io::Result<String> {
+    fs::read_to_string(aoc_cli::get_puzzle_path(day))
+}
+
+/// Writes `example` to the example file for `day`, unless that file already has content.
+/// Returns whether the file was written.
+///
+/// # Errors
+///
+/// Will return `io::Error` if the example file could not be read or written.
+pub fn write_example(day: Day, example: &str) -> io::Result<bool> {
+    let example_path = format!("data/examples/{day}.txt");
+
+    if fs::read_to_string(&example_path).is_ok_and(|existing| !existing.trim().is_empty()) {
+        return Ok(false);
+    }
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&example_path)?;
+    file.write_all(example.as_bytes())?;
+    Ok(true)
+}
+
+/// Splits the markdown written by aoc-cli into headings, paragraphs, list items and code blocks.
+#[must_use]
+pub fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut pending: Option<Block> = None;
+    let mut lines = markdown.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || is_link_reference(trimmed) {
+            blocks.extend(pending.take());
+        } else if trimmed.starts_with("```") {
+            blocks.extend(pending.take());
+            let code: Vec<&str> = lines
+                .by_ref()
+                .take_while(|line| !line.trim_start().starts_with("```"))
+                .collect();
+            blocks.push(Block::Code(code.join("\n")));
+        } else if let Some(first) = trimmed.strip_prefix("<pre><code>") {
+            blocks.extend(pending.take());
+            let mut code = vec![first];
+            let mut last = first;
+            while !last.contains("</code></pre>") {
+                let Some(next) = lines.next() else {
+                    break;
+                };
+                code.push(next);
+                last = next;
+            }
+            let code = code.join("\n");
+            let code = code.split("</code></pre>").next().unwrap_or_default();
+            blocks.push(Block::Code(decode_html(code.trim_end_matches('\n'))));
+        } else if pending.is_none() && (line.starts_with("    ") || line.starts_with('\t')) {
+            let mut code = vec![&line[line.len() - line.trim_start().len()..]];
+            while let Some(next) = lines.next_if(|next| {
+                next.trim().is_empty() || next.starts_with("    ") || next.starts_with('\t')
+            }) {
+                code.push(
+                    next.strip_prefix("    ")
+                        .unwrap_or_else(|| next.trim_start()),
+                );
+            }
+            while code.last().is_some_and(|line| line.trim().is_empty()) {
+                code.pop();
+            }
+            blocks.push(Block::Code(code.join("\n")));
+        } else if trimmed.starts_with('#') {
+            blocks.extend(pending.take());
+            blocks.push(Block::Heading(trimmed.trim_matches('#').trim().to_string()));
+        } else if trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-' || c == '=') {
+            match pending.take() {
+                Some(Block::Paragraph(text)) => blocks.push(Block::Heading(text)),
+                other => blocks.extend(other),
+            }
+        } else if let Some(item) = ["* ", "- ", "+ "]
+            .iter()
+            .find_map(|bullet| trimmed.strip_prefix(bullet))
+        {
+            blocks.extend(pending.take());
+            pending = Some(Block::ListItem(item.to_string()));
+        } else {
+            match &mut pending {
+                Some(Block::Paragraph(text) | Block::ListItem(text)) => {
+                    text.push(' ');
+                    text.push_str(trimmed);
+                }
+                _ => pending = Some(Block::Paragraph(trimmed.to_string())),
+            }
+        }
+    }
+
+    blocks.extend(pending);
+    blocks
+}
+
+/// Returns the contents of every code block, which is where the puzzle examples live.
+#[must_use]
+pub fn code_blocks(blocks: &[Block]) -> Vec<&str> {
+    blocks
+        .iter()
+        .filter_map(|block| match block {
+            Block::Code(code) => Some(code.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the puzzle title from the first heading, e.g. "Trebuchet?!" from
+/// "--- Day 1: Trebuchet?! ---".
+#[must_use]
+pub fn title(blocks: &[Block]) -> Option<String> {
+    let heading = blocks.iter().find_map(|block| match block {
+        Block::Heading(text) => Some(strip_markup(text)),
+        _ => None,
+    })?;
+    let heading = heading.trim_matches(|c: char| c == '-' || c.is_whitespace());
+    let title = heading.split_once(": ").map_or(heading, |(_, title)| title);
+    Some(title.to_string())
+}
+
+fn part_two_start(blocks: &[Block]) -> Option<usize> {
+    blocks
+        .iter()
+        .position(|block| matches!(block, Block::Heading(text) if text.contains("Part Two")))
+}
+
+/// Returns the first code block in the part two section, for puzzles which give part two an
+/// example of its own.
+#[must_use]
+pub fn part_two_example(blocks: &[Block]) -> Option<&str> {
+    let start = part_two_start(blocks)?;
+    code_blocks(&blocks[start..]).first().copied()
+}
+
+/// Finds the answer to the example for the given part, which is the last emphasised code span
+/// (e.g. `` `*142*` ``) in the paragraphs describing that part.
+#[must_use]
+pub fn example_answer(blocks: &[Block], part: u8) -> Option<String> {
+    let part_two_start = part_two_start(blocks);
+
+    let section = match (part, part_two_start) {
+        (1, Some(start)) => &blocks[..start],
+        (1, None) => blocks,
+        (2, Some(start)) => &blocks[start..],
+        _ => return None,
+    };
+
+    section.iter().rev().find_map(|block| match block {
+        Block::Paragraph(text) | Block::ListItem(text) => emphasised_code(text).pop(),
+        _ => None,
+    })
+}
+
+fn emphasised_code(text: &str) -> Vec<String> {
+    let markdown = text
+        .split("`*")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("*`").map(|(value, _)| value));
+    let html = text
+        .split("<code><em>")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("</em></code>").map(|(value, _)| value));
+
+    markdown.chain(html).map(strip_markup).collect()
+}
+
+/// Renders the blocks with ANSI styling, wrapping paragraphs at `width` columns.
+#[must_use]
+pub fn render(blocks: &[Block], width: usize) -> String {
+    let mut output = Vec::new();
+    let mut code_block = 0;
+
+    for (ix, block) in blocks.iter().enumerate() {
+        match block {
+            Block::Heading(text) => {
+                output.push(format!("{ANSI_BOLD}{}{ANSI_RESET}", strip_markup(text)));
+                output.push(String::new());
+            }
+            Block::Paragraph(text) => {
+                output.push(wrap(&render_inline(text), width, "", ""));
+                output.push(String::new());
+            }
+            Block::ListItem(text) => {
+                output.push(wrap(&render_inline(text), width, "  • ", "    "));
+                if !matches!(blocks.get(ix + 1), Some(Block::ListItem(_))) {
+                    output.push(String::new());
+                }
+            }
+            Block::Code(code) => {
+                code_block += 1;
+                output.push(format!("{ANSI_ITALIC}[block {code_block}]{ANSI_RESET}"));
+                output.extend(code.lines().map(|line| format!("    {line}")));
+                output.push(String::new());
+            }
+        }
+    }
+
+    output.join("\n")
+}
+
+fn is_link_reference(line: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once("]: "))
+        .is_some_and(|(label, _)| label.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn decode_html(text: &str) -> String {
+    let mut output = String::new();
+    let mut in_tag = false;
+
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => output.push(c),
+            _ => (),
+        }
+    }
+
+    output
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Finds a `[text][ref]` or `[text](url)` link at the start of `text`, returning the link text
+/// and the length of the whole link.
+fn find_link(text: &str) -> Option<(&str, usize)> {
+    let close = text.find(']')?;
+    let (open, end) = match text[close + 1..].chars().next()? {
+        '[' => ('[', ']'),
+        '(' => ('(', ')'),
+        _ => return None,
+    };
+    let target = text[close + 1..].strip_prefix(open)?;
+    let length = close + 2 + target.find(end)? + 1;
+    Some((&text[1..close], length))
+}
+
+/// Removes the markdown markup (emphasis, code spans, links and escapes) from `text`.
+fn strip_markup(text: &str) -> String {
+    let mut output = String::new();
+    let mut ix = 0;
+
+    while let Some(c) = text[ix..].chars().next() {
+        ix += c.len_utf8();
+        match c {
+            '\\' => {
+                if let Some(escaped) = text[ix..].chars().next() {
+                    output.push(escaped);
+                    ix += escaped.len_utf8();
+                }
+            }
+            '*' | '`' => (),
+            '[' => {
+                if let Some((link, length)) = find_link(&text[ix - 1..]) {
+                    output.push_str(&strip_markup(link));
+                    ix += length - 1;
+                } else {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Converts markdown emphasis into ANSI styling, dropping the remaining markup.
+fn render_inline(text: &str) -> String {
+    let mut output = String::new();
+    let mut bold = false;
+    let mut italic = false;
+    let mut ix = 0;
+
+    while let Some(c) = text[ix..].chars().next() {
+        ix += c.len_utf8();
+        match c {
+            '\\' => {
+                if let Some(escaped) = text[ix..].chars().next() {
+                    output.push(escaped);
+                    ix += escaped.len_utf8();
+                }
+            }
+            '*' => {
+                if text[ix..].starts_with('*') {
+                    ix += 1;
+                    bold = !bold;
+                } else {
+                    italic = !italic;
+                }
+                output.push_str(ANSI_RESET);
+                if bold {
+                    output.push_str(ANSI_BOLD);
+                }
+                if italic {
+                    output.push_str(ANSI_ITALIC);
+                }
+            }
+            '`' => (),
+            '[' => {
+                if let Some((link, length)) = find_link(&text[ix - 1..]) {
+                    output.push_str(&render_inline(link));
+                    ix += length - 1;
+                } else {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    if bold || italic {
+        output.push_str(ANSI_RESET);
+    }
+    output
+}
+
+/// The number of characters in `text` which will be visible, ignoring ANSI escape sequences.
+fn visible_length(text: &str) -> usize {
+    let mut length = 0;
+    let mut in_escape = false;
+
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if !in_escape => length += 1,
+            _ => (),
+        }
+    }
+
+    length
+}
+
+fn wrap(text: &str, width: usize, first_prefix: &str, prefix: &str) -> String {
+    let mut lines = Vec::new();
+    let mut line = first_prefix.to_string();
+    let mut line_length = visible_length(first_prefix);
+    let mut line_empty = true;
+
+    for word in text.split_whitespace() {
+        let word_length = visible_length(word);
+        if !line_empty && line_length + 1 + word_length > width {
+            lines.push(line);
+            line = prefix.to_string();
+            line_length = visible_length(prefix);
+            line_empty = true;
+        }
+        if !line_empty {
+            line.push(' ');
+            line_length += 1;
+        }
+        line.push_str(word);
+        line_length += word_length;
+        line_empty = false;
+    }
+
+    lines.push(line);
+    lines.join("\n")
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{
+        code_blocks, example_answer, parse_blocks, part_two_example, render_inline, strip_markup,
+        title, visible_length, wrap,
+    };
+    use super::{Block, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+
+    const PUZZLE: &str = r"\--- Day 1: Trebuchet?! ---
+----------
+
+Something is wrong with global snow production, and you've been selected to take a look. The
+Elves have even given you a [map][1].
+
+For example:
+
+```
+1abc2
+pqr3stu8vwx
+```
+
+In this example, adding these together produces `*142*`.
+
+* First item
+* Second item
+
+[1]: /2023/day/1/map
+";
+
+    #[test]
+    fn test_parse_blocks() {
+        assert_eq!(
+            parse_blocks(PUZZLE),
+            vec![
+                Block::Heading(r"\--- Day 1: Trebuchet?! ---".to_string()),
+                Block::Paragraph(
+                    "Something is wrong with global snow production, and you've been selected \
+                    to take a look. The Elves have even given you a [map][1]."
+                        .to_string()
+                ),
+                Block::Paragraph("For example:".to_string()),
+                Block::Code("1abc2\npqr3stu8vwx".to_string()),
+                Block::Paragraph(
+                    "In this example, adding these together produces `*142*`.".to_string()
+                ),
+                Block::ListItem("First item".to_string()),
+                Block::ListItem("Second item".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_title() {
+        assert_eq!(
+            title(&parse_blocks(PUZZLE)),
+            Some("Trebuchet?!".to_string())
+        );
+        assert_eq!(title(&parse_blocks("No headings here.")), None);
+    }
+
+    #[test]
+    fn test_example_answer() {
+        let blocks = parse_blocks(PUZZLE);
+        assert_eq!(example_answer(&blocks, 1), Some("142".to_string()));
+        assert_eq!(example_answer(&blocks, 2), None);
+
+        let both_parts =
+            format!("{PUZZLE}\n\\--- Part Two ---\n----------\n\nNow it is `*281*`, not `12`.\n");
+        let blocks = parse_blocks(&both_parts);
+        assert_eq!(example_answer(&blocks, 1), Some("142".to_string()));
+        assert_eq!(example_answer(&blocks, 2), Some("281".to_string()));
+    }
+
+    #[test]
+    fn test_part_two_example() {
+        assert_eq!(part_two_example(&parse_blocks(PUZZLE)), None);
+
+        let same_example =
+            format!("{PUZZLE}\n\\--- Part Two ---\n----------\n\nNow it is `*281*`.\n");
+        assert_eq!(part_two_example(&parse_blocks(&same_example)), None);
+
+        let new_example = format!(
+            "{PUZZLE}\n\\--- Part Two ---\n----------\n\n```\ntwo1nine\n```\n\nNow it is `*281*`.\n"
+        );
+        assert_eq!(
+            part_two_example(&parse_blocks(&new_example)),
+            Some("two1nine")
+        );
+    }
+
+    #[test]
+    fn test_parse_html_and_indented_code() {
+        let markdown =
+            "Example:\n\n<pre><code>a &lt; <em>b</em>\nc\n</code></pre>\n\n    x\n    y\n";
+        assert_eq!(
+            code_blocks(&parse_blocks(markdown)),
+            vec!["a < b\nc", "x\ny"]
+        );
+    }
+
+    #[test]
+    fn test_strip_markup() {
+        assert_eq!(
+            strip_markup(r"\--- Day 1: [The *Map*][2] ---"),
+            "--- Day 1: The Map ---"
+        );
+    }
+
+    #[test]
+    fn test_render_inline() {
+        assert_eq!(
+            render_inline("a `*142*` and **b** [link](url)"),
+            format!("a {ANSI_RESET}{ANSI_ITALIC}142{ANSI_RESET} and {ANSI_RESET}{ANSI_BOLD}b{ANSI_RESET} link")
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let styled = format!("one {ANSI_BOLD}two{ANSI_RESET} three four");
+        assert_eq!(visible_length(&styled), 18);
+        assert_eq!(
+            wrap(&styled, 8, "* ", "  "),
+            format!("* one\n  {ANSI_BOLD}two{ANSI_RESET}\n  three\n  four")
+        );
+        assert_eq!(wrap("one two three", 80, "", ""), "one two three");
+    }
+}
@@ -27,6 +27,34 @@ pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, d
     }
 }
 
+/// Returns `true` if `flag` was passed on the command line, e.g. `cargo solve 5 --explain`.
+#[must_use]
+pub fn has_flag(flag: &str) -> bool {
+    env::args().any(|arg| arg == flag)
+}
+
+/// Returns the argument following `flag` on the command line, e.g. the path in
+/// `cargo solve 16 --export beams.png`.
+#[must_use]
+pub fn flag_value(flag: &str) -> Option<String> {
+    env::args().skip_while(|arg| arg != flag).nth(1)
+}
+
+/// Benchmarks `func` in the same way that `--time` benchmarks a solution part, printing its
+/// average running time under `name`. For days which compare alternative implementations.
+pub fn benchmark<I: Clone, T>(name: &str, func: impl Fn(I) -> T, input: I) {
+    print!("{name}:");
+    let timer = Instant::now();
+    func(input.clone());
+    let base_time = timer.elapsed();
+
+    let (duration, samples) = bench(func, input, &base_time);
+    println!(
+        "\r{name}:{}             ",
+        format_duration(&duration, samples)
+    );
+}
+
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
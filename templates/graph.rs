@@ -0,0 +1,85 @@
+// Advent of Code YEAR_NUMBER, day DAY_NUMBER: PUZZLE_TITLE
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+advent_of_code::solution!(DAY_NUMBER);
+
+#[derive(Debug, Default, PartialEq)]
+struct Graph {
+    edges: HashMap<String, Vec<String>>,
+}
+
+#[allow(dead_code)]
+impl Graph {
+    fn distances_from<'a>(&'a self, start: &'a str) -> HashMap<&'a str, u32> {
+        let mut distances = HashMap::new();
+        let mut queue = VecDeque::new();
+
+        distances.insert(start, 0);
+        queue.push_back((start, 0));
+
+        while let Some((node, distance)) = queue.pop_front() {
+            for next in self.edges.get(node).into_iter().flatten() {
+                if !distances.contains_key(next.as_str()) {
+                    distances.insert(next.as_str(), distance + 1);
+                    queue.push_back((next.as_str(), distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseGraphError;
+
+impl FromStr for Graph {
+    type Err = ParseGraphError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut graph = Self::default();
+
+        for line in input.lines() {
+            let (source, destinations) = line.split_once(": ").ok_or(ParseGraphError)?;
+            for destination in destinations.split_whitespace() {
+                graph
+                    .edges
+                    .entry(source.to_string())
+                    .or_default()
+                    .push(destination.to_string());
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+#[must_use]
+pub fn part_one(input: &str) -> Option<u32> {
+    let _graph = Graph::from_str(input).ok()?;
+    None
+}
+
+#[must_use]
+pub fn part_two(input: &str) -> Option<u32> {
+    let _graph = Graph::from_str(input).ok()?;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, PART_ONE_ANSWER);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&PART_TWO_EXAMPLE);
+        assert_eq!(result, PART_TWO_ANSWER);
+    }
+}
@@ -0,0 +1,109 @@
+// Advent of Code YEAR_NUMBER, day DAY_NUMBER: PUZZLE_TITLE
+use std::str::FromStr;
+
+advent_of_code::solution!(DAY_NUMBER);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tile {
+    Empty,
+    Wall,
+}
+
+#[derive(Debug, PartialEq)]
+struct Grid {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+#[allow(dead_code)]
+impl Grid {
+    const fn position(&self, row: usize, col: usize) -> usize {
+        (row * self.width) + col
+    }
+
+    fn neighbours(&self, position: usize) -> impl Iterator<Item = usize> + '_ {
+        let row = position / self.width;
+        let col = position % self.width;
+        [
+            row.checked_sub(1).map(|row| self.position(row, col)),
+            (col + 1 < self.width).then(|| self.position(row, col + 1)),
+            (row + 1 < self.height).then(|| self.position(row + 1, col)),
+            col.checked_sub(1).map(|col| self.position(row, col)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct ParseGridError;
+
+impl TryFrom<char> for Tile {
+    type Error = ParseGridError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Empty),
+            '#' => Ok(Self::Wall),
+            _ => Err(ParseGridError),
+        }
+    }
+}
+
+impl FromStr for Grid {
+    type Err = ParseGridError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut tiles = Vec::new();
+
+        for line in input.lines() {
+            width = line.len();
+            height += 1;
+            for ch in line.chars() {
+                tiles.push(Tile::try_from(ch)?);
+            }
+        }
+
+        if tiles.len() == width * height {
+            Ok(Self {
+                width,
+                height,
+                tiles,
+            })
+        } else {
+            Err(ParseGridError)
+        }
+    }
+}
+
+#[must_use]
+pub fn part_one(input: &str) -> Option<u32> {
+    let _grid = Grid::from_str(input).ok()?;
+    None
+}
+
+#[must_use]
+pub fn part_two(input: &str) -> Option<u32> {
+    let _grid = Grid::from_str(input).ok()?;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, PART_ONE_ANSWER);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&PART_TWO_EXAMPLE);
+        assert_eq!(result, PART_TWO_ANSWER);
+    }
+}
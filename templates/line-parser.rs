@@ -0,0 +1,49 @@
+// Advent of Code YEAR_NUMBER, day DAY_NUMBER: PUZZLE_TITLE
+use advent_of_code::parse::{parse_all, ParseError, Parser};
+
+advent_of_code::solution!(DAY_NUMBER);
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    values: Vec<i64>,
+}
+
+impl Entry {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let values = parser.list(Parser::number)?;
+        Ok(Self { values })
+    }
+}
+
+fn read_entries(input: &str) -> Result<Vec<Entry>, ParseError> {
+    parse_all(input, |parser| parser.lines(Entry::parse))
+}
+
+#[must_use]
+pub fn part_one(input: &str) -> Option<u32> {
+    let _entries = read_entries(input).ok()?;
+    None
+}
+
+#[must_use]
+pub fn part_two(input: &str) -> Option<u32> {
+    let _entries = read_entries(input).ok()?;
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, PART_ONE_ANSWER);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&PART_TWO_EXAMPLE);
+        assert_eq!(result, PART_TWO_ANSWER);
+    }
+}